@@ -1,6 +1,70 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use parson::Parson;
-use std::{fs, hint::black_box, str::from_utf8};
+use parson::{JsonInterner, Parson};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fs,
+    hint::black_box,
+    str::from_utf8,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Counts the bytes currently allocated, to report how much memory each parse
+/// mode keeps alive.
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATED.fetch_add(new_size, Ordering::Relaxed);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Bytes still allocated after `parse` returns, i.e. the size of the tree.
+fn retained<T>(parse: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let value = parse();
+    (value, ALLOCATED.load(Ordering::Relaxed) - before)
+}
+
+fn report_memory(bytes: &[u8]) {
+    let (_plain, plain) = retained(|| Parson::parse_json_with_bytes(bytes).unwrap());
+    let modes = [
+        (
+            "interned keys",
+            retained(|| Parson::parse_json_interned(bytes).unwrap()).1,
+        ),
+        (
+            "interned keys and short strings",
+            retained(|| JsonInterner::new().strings_up_to(16).parse(bytes).unwrap()).1,
+        ),
+        (
+            "arena",
+            retained(|| Parson::parse_json_arena(bytes).unwrap()).1,
+        ),
+    ];
+    println!("json memory retained, {} byte input:", bytes.len());
+    println!("  {:<32} {:>9} bytes", "plain", plain);
+    for (mode, size) in modes {
+        let saved = 100.0 - size as f64 * 100.0 / plain as f64;
+        println!("  {:<32} {:>9} bytes ({:.0}% less)", mode, size, saved);
+    }
+}
 
 fn get_json_mock_data_path() -> String {
     let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
@@ -18,6 +82,13 @@ fn read_json_bytes(bytes: &[u8]) {
     let _ = parsed_json.unwrap();
 }
 
+#[cfg(feature = "rayon")]
+fn read_json_bytes_parallel(bytes: &[u8]) {
+    let parsed_json = Parson::parse_json_parallel(bytes);
+    assert!(parsed_json.is_ok());
+    let _ = parsed_json.unwrap();
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     let bytes = fs::read(get_json_mock_data_path()).unwrap();
     let json_string = from_utf8(&bytes).unwrap();
@@ -27,6 +98,17 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("json_bytes", |b| {
         b.iter(|| read_json_bytes(black_box(&bytes)))
     });
+    report_memory(&bytes);
+    c.bench_function("json_bytes_interned", |b| {
+        b.iter(|| Parson::parse_json_interned(black_box(&bytes)).unwrap())
+    });
+    c.bench_function("json_bytes_arena", |b| {
+        b.iter(|| Parson::parse_json_arena(black_box(&bytes)).unwrap())
+    });
+    #[cfg(feature = "rayon")]
+    c.bench_function("json_bytes_parallel", |b| {
+        b.iter(|| read_json_bytes_parallel(black_box(&bytes)))
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -1,15 +1,33 @@
+mod options;
+mod record;
+mod schema;
+#[cfg(feature = "async")]
+mod stream;
 mod token;
 mod value;
+mod words;
 
-use std::{collections::HashMap, io::BufReader};
+use std::{collections::HashMap, str::from_utf8, sync::Arc};
 
-use token::{Token, Tokenizer};
+pub use options::{CsvDialect, CsvEncoding, CsvEscape, CsvHeader, CsvOptions, CsvTerminator};
+use record::Header;
+pub use record::{CsvDuplicateHeaders, CsvRecord};
+use schema::Typing;
+pub use schema::{CsvColumn, CsvSchema, CsvType};
+#[cfg(feature = "async")]
+pub use stream::CsvRecordStream;
+pub use token::Number;
+use token::{Field, Token, Tokenizer};
 pub use value::Value;
 
 use crate::{ParsingError, ParsonResult};
 
 pub struct CsvParser {
-    tokenizer: Tokenizer,
+    header: Arc<Header>,
+    rows: Vec<Vec<Field>>,
+    lines: Vec<usize>,
+    typing: Typing,
+    lossy_lines: Vec<usize>,
 }
 
 impl TryFrom<&Token> for String {
@@ -27,52 +45,216 @@ impl TryFrom<&Token> for String {
 
 impl CsvParser {
     pub fn new(buf: &[u8]) -> ParsonResult<Self> {
-        let bufread = BufReader::new(buf);
+        Self::with_options(buf, &CsvOptions::default())
+    }
+
+    pub fn with_options(buf: &[u8], options: &CsvOptions) -> ParsonResult<Self> {
+        options.dialect.validate()?;
+        let (buf, lossy_offsets) = options.encoding.decode(buf)?;
+        let text = from_utf8(&buf).map_err(|_| ParsingError {
+            message: "failed to tokenize buf".to_string(),
+        })?;
+        let Tokenizer {
+            records: mut rows,
+            mut lines,
+            offsets,
+        } = Tokenizer::new(text, &options.dialect, options.skip_lines)?;
+        let lossy_lines = record_lines(&lossy_offsets, &offsets, &lines);
+        let (header, typing, first_is_header) =
+            read_header(options, rows.first().map(Vec::as_slice))?;
+        if first_is_header {
+            rows.remove(0);
+            lines.remove(0);
+        }
         Ok(Self {
-            tokenizer: Tokenizer::new(bufread)?,
+            typing,
+            header: Arc::new(header),
+            rows,
+            lines,
+            lossy_lines,
         })
     }
 
+    /// The lines of the records, header included, in which invalid bytes were
+    /// replaced when decoding with [`CsvEncoding::Utf8Lossy`]. A record is
+    /// given by the line it starts on, counted as in error messages, and
+    /// lines skipped with [`CsvOptions::skip_lines`] are not reported.
+    pub fn lossy_lines(&self) -> &[usize] {
+        &self.lossy_lines
+    }
+
+    /// The column names, from the file or from [`CsvOptions::header`].
+    pub fn header(&self) -> &[String] {
+        &self.header.names
+    }
+
+    /// Parses the records into maps from column name to value. Columns that
+    /// share a name are renamed by the default [`CsvDuplicateHeaders::Suffix`],
+    /// so a header `b,a,b` gives the keys `b`, `a` and `b_2`, with `b` holding
+    /// the first column rather than the last. With
+    /// [`CsvDuplicateHeaders::KeepAll`], only the first of the columns sharing
+    /// a name is kept, as [`CsvRecord::get`] returns it;
+    /// [`CsvParser::parse_records`] keeps them all.
     pub fn parse(&self) -> ParsonResult<Vec<HashMap<String, Value>>> {
-        let mut value = vec![];
-
-        let mut header: Vec<String> = vec![];
-
-        for (idx, line) in self.tokenizer.tokens.iter().enumerate() {
-            match idx {
-                0 => {
-                    // this is the header
-                    // all row items must be strings
-                    if line.iter().any(|item| !matches!(*item, Token::String(..))) {
-                        return Err(ParsingError {
-                            message: "header items must all be strings".to_string(),
-                        });
-                    }
-                    header = line
-                        .iter()
-                        .filter(|t| matches!(t, Token::String(..)))
-                        .map(|val| val.try_into())
-                        .collect::<ParsonResult<Vec<String>>>()?;
-                }
-                _ => {
-                    let row_values = line
-                        .iter()
-                        .zip(&header)
-                        .map(|(row, col)| (col.to_string(), row.into()))
-                        .collect::<HashMap<String, Value>>();
-
-                    value.push(row_values);
+        self.rows
+            .iter()
+            .zip(&self.lines)
+            .map(|(row, line)| parse_row(&self.header.names, &self.typing, row, *line))
+            .collect()
+    }
+
+    /// Parses the records keeping their column order, all records sharing
+    /// one copy of the header.
+    pub fn parse_records(&self) -> ParsonResult<Vec<CsvRecord>> {
+        self.parse_rows().map(|rows| {
+            rows.into_iter()
+                .map(|values| CsvRecord::new(self.header.clone(), values))
+                .collect()
+        })
+    }
+
+    /// Parses the records giving each column a single type, inferred from the
+    /// whole file or from [`CsvOptions::inference_sample`] rows, and returns
+    /// that schema with the typed records.
+    pub fn parse_with_schema(&self) -> ParsonResult<(CsvSchema, Vec<CsvRecord>)> {
+        let names = &self.header.names;
+        let types = self.typing.infer(&self.rows, names.len());
+        let mut columns = names
+            .iter()
+            .zip(types)
+            .map(|(name, (csv_type, format))| CsvColumn {
+                name: name.clone(),
+                csv_type,
+                format,
+                nullable: false,
+                nulls: 0,
+                mismatches: 0,
+            })
+            .collect::<Vec<_>>();
+
+        let mut records = Vec::with_capacity(self.rows.len());
+        for (row, line) in self.rows.iter().zip(&self.lines) {
+            let mut values = Vec::with_capacity(row.len());
+            for (idx, field) in row.iter().enumerate() {
+                let column = &mut columns[idx];
+                let value = match self.typing.hint(idx) {
+                    Some(_) => self.typing.value(field, idx, &column.name, *line)?,
+                    None if self.typing.words(idx).is_null(field) => Value::Null,
+                    None => self
+                        .typing
+                        .read_as(idx, column.csv_type, column.format.as_slice(), field)
+                        .unwrap_or_else(|| {
+                            column.mismatches += 1;
+                            Value::String(field.value.clone())
+                        }),
+                };
+                if value == Value::Null {
+                    column.nulls += 1;
+                    column.nullable = true;
                 }
-            };
+                values.push(value);
+            }
+            records.push(CsvRecord::new(self.header.clone(), values));
+        }
+        Ok((CsvSchema { columns }, records))
+    }
+
+    /// Parses the records as rows of values in column order, without the header.
+    pub fn parse_rows(&self) -> ParsonResult<Vec<Vec<Value>>> {
+        self.rows
+            .iter()
+            .zip(&self.lines)
+            .map(|(row, line)| parse_values(&self.header.names, &self.typing, row, *line))
+            .collect()
+    }
+}
+
+/// The header `options` give for a file whose first record is `first`, and
+/// the typing of its columns. Also tells whether `first` is the header rather
+/// than data.
+fn read_header(
+    options: &CsvOptions,
+    first: Option<&[Field]>,
+) -> ParsonResult<(Header, Typing, bool)> {
+    let columns = first.map_or(0, <[Field]>::len);
+    let (names, first_is_header) = match (&options.header, first) {
+        (CsvHeader::FirstRow, None) => (vec![], false),
+        (CsvHeader::FirstRow, Some(first)) => (parse_header(first)?, true),
+        (CsvHeader::None, _) => (
+            (1..=columns).map(|idx| format!("column_{}", idx)).collect(),
+            false,
+        ),
+        (CsvHeader::Names(names), Some(_)) if names.len() != columns => {
+            return Err(ParsingError {
+                message: format!("{} column names given for {} columns", names.len(), columns),
+            })
         }
+        (CsvHeader::Names(names), _) => (names.clone(), false),
+    };
+    let header = Header::new(options.duplicate_headers.apply(names)?);
+    let typing = Typing::new(options, &header.names)?;
+    Ok((header, typing, first_is_header))
+}
 
-        Ok(value)
+/// The start lines of the records holding the byte `offsets`, each once.
+/// Offsets before the first record, in skipped lines, are left out.
+fn record_lines(offsets: &[usize], starts: &[usize], lines: &[usize]) -> Vec<usize> {
+    let mut record_lines: Vec<usize> = vec![];
+    for offset in offsets {
+        let records = starts.partition_point(|start| start <= offset);
+        let Some(line) = records.checked_sub(1).map(|idx| lines[idx]) else {
+            continue;
+        };
+        if record_lines.last() != Some(&line) {
+            record_lines.push(line);
+        }
     }
+    record_lines
+}
+
+fn parse_header(line: &[Field]) -> ParsonResult<Vec<String>> {
+    // header items are names whatever they look like, but can't be missing
+    if line.iter().any(|item| item.value.is_empty()) {
+        return Err(ParsingError {
+            message: "header items must not be empty".to_string(),
+        });
+    }
+    Ok(line.iter().map(|item| item.value.clone()).collect())
+}
+
+fn parse_values(
+    header: &[String],
+    typing: &Typing,
+    row: &[Field],
+    line: usize,
+) -> ParsonResult<Vec<Value>> {
+    row.iter()
+        .zip(header)
+        .enumerate()
+        .map(|(idx, (field, column))| typing.value(field, idx, column, line))
+        .collect()
+}
+
+fn parse_row(
+    header: &[String],
+    typing: &Typing,
+    row: &[Field],
+    line: usize,
+) -> ParsonResult<HashMap<String, Value>> {
+    let mut values = HashMap::with_capacity(header.len());
+    for (idx, (field, column)) in row.iter().zip(header).enumerate() {
+        let value = typing.value(field, idx, column, line)?;
+        values.entry(column.to_string()).or_insert(value);
+    }
+    Ok(values)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::CsvParser;
+    use super::{
+        CsvDialect, CsvDuplicateHeaders, CsvEncoding, CsvHeader, CsvOptions, CsvParser, CsvType,
+        Number, Value,
+    };
 
     #[test]
     fn parsing_correctly() {
@@ -90,4 +272,254 @@ mod tests {
 
         assert_eq!(parsedcsv.unwrap().iter().len(), 2);
     }
+
+    #[test]
+    fn decode_legacy_and_lossy_input() {
+        let bytes = b"name,price\nCaf\xE9,\x80 3\n";
+        assert!(CsvParser::new(bytes).is_err());
+
+        let options = CsvOptions::new().encoding(CsvEncoding::Windows1252);
+        let parsed = CsvParser::with_options(bytes, &options)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(matches!(&parsed[0]["name"], Value::String(name) if name == "Café"));
+        assert!(matches!(&parsed[0]["price"], Value::String(price) if price == "€ 3"));
+
+        let options = CsvOptions::new().encoding(CsvEncoding::Utf8Lossy);
+        let parser = CsvParser::with_options(bytes, &options).unwrap();
+        assert_eq!(parser.lossy_lines(), &[2]);
+        let parsed = parser.parse().unwrap();
+        assert!(matches!(&parsed[0]["name"], Value::String(name) if name == "Caf\u{FFFD}"));
+
+        let bytes = b"export \xFF\nname,note\nx,\"two\nli\xFFnes\"\ny,\xFF\n";
+        let options = CsvOptions::new()
+            .encoding(CsvEncoding::Utf8Lossy)
+            .skip_lines(1);
+        let parser = CsvParser::with_options(bytes, &options).unwrap();
+        assert_eq!(parser.lossy_lines(), &[3, 5]);
+    }
+
+    #[test]
+    fn parse_with_a_dialect() {
+        let options = CsvOptions::new().dialect(CsvDialect::new().delimiter(";"));
+        let parsed = CsvParser::with_options(b"name;price\nCaf\xC3\xA9;3,50\n", &options)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(matches!(&parsed[0]["price"], Value::String(price) if price == "3,50"));
+
+        for dialect in [
+            CsvDialect::new().delimiter(""),
+            CsvDialect::new().delimiter("\""),
+            CsvDialect::new().delimiter("\n"),
+            CsvDialect::unix().delimiter("\\"),
+        ] {
+            let options = CsvOptions::new().dialect(dialect);
+            let error = CsvParser::with_options(b"a", &options).err().unwrap();
+            assert!(error.message.starts_with("invalid csv dialect"));
+        }
+    }
+
+    #[test]
+    fn parse_with_header_options() {
+        let parsed = CsvParser::new(b"2023,2024\n1.5,2\n")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(matches!(
+            parsed[0]["2023"],
+            Value::Number(Number::Float(1.5))
+        ));
+        assert!(CsvParser::new(b"a,\n1,2\n").is_err());
+
+        let options = CsvOptions::new().header(CsvHeader::None);
+        let parser = CsvParser::with_options(b"1,x\n2,y\n", &options).unwrap();
+        assert_eq!(parser.header(), &["column_1", "column_2"]);
+        let rows = parser.parse_rows().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(matches!(&rows[1][1], Value::String(y) if y == "y"));
+
+        let names = CsvHeader::Names(vec!["id".to_string(), "name".to_string()]);
+        let options = CsvOptions::new().header(names.clone());
+        let parsed = CsvParser::with_options(b"1,x\n2,y\n", &options)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(matches!(parsed[1]["id"], Value::Number(Number::Int(2))));
+        assert!(CsvParser::with_options(b"1,x,z\n", &options).is_err());
+
+        let options = CsvOptions::new().skip_lines(2);
+        let parser = CsvParser::with_options(
+            b"Sales report\nexported 2024-01-02\nid,name\n1,x\n",
+            &options,
+        )
+        .unwrap();
+        assert_eq!(parser.header(), &["id", "name"]);
+        assert_eq!(parser.parse().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn parse_records_in_column_order() {
+        let csv_string = "b,a,b\n1,x,2\n3,y,4\n";
+        let records = CsvParser::new(csv_string.as_bytes())
+            .unwrap()
+            .parse_records()
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].header(), &["b", "a", "b_2"]);
+        assert!(matches!(records[1]["b_2"], Value::Number(Number::Int(4))));
+        assert!(matches!(&records[1][1], Value::String(y) if y == "y"));
+
+        let options = CsvOptions::new().duplicate_headers(CsvDuplicateHeaders::KeepAll);
+        let records = CsvParser::with_options(csv_string.as_bytes(), &options)
+            .unwrap()
+            .parse_records()
+            .unwrap();
+        assert_eq!(records[0].get_all("b").count(), 2);
+        assert!(matches!(records[0]["b"], Value::Number(Number::Int(1))));
+        let parsed = CsvParser::with_options(csv_string.as_bytes(), &options)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(matches!(parsed[0]["b"], Value::Number(Number::Int(1))));
+        assert_eq!(parsed[0].len(), 2);
+
+        let options = CsvOptions::new().duplicate_headers(CsvDuplicateHeaders::Error);
+        assert!(CsvParser::with_options(csv_string.as_bytes(), &options).is_err());
+    }
+
+    #[test]
+    fn parse_with_type_hints() {
+        let csv_string = "id,account,zip,price,\"note\"\n1,3588358312619246,02134,12.50,\"42\"\n";
+        let options = CsvOptions::new()
+            .column_type("zip", CsvType::String)
+            .column_type("price", CsvType::Decimal);
+        let parsed = CsvParser::with_options(csv_string.as_bytes(), &options)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(
+            parsed[0]["account"],
+            Value::Number(Number::Int(3588358312619246))
+        );
+        assert_eq!(parsed[0]["zip"], Value::String("02134".to_string()));
+        assert_eq!(
+            parsed[0]["price"],
+            Value::Number(Number::Decimal("12.50".to_string()))
+        );
+        assert_eq!(parsed[0]["note"], Value::String("42".to_string()));
+
+        let options = CsvOptions::new().column_type("zip", CsvType::Int);
+        let error = CsvParser::with_options(b"zip\n123\nabc\n", &options)
+            .unwrap()
+            .parse()
+            .unwrap_err();
+        assert_eq!(
+            error.message,
+            "line 3, column \"zip\": cannot read \"abc\" as int"
+        );
+    }
+
+    #[test]
+    fn parse_with_an_inferred_schema() {
+        let csv_string = "id,score,zip,label\n1,1,02134,a\n2,2.5,10001,\n3,,\"x\",b\n";
+        let parser = CsvParser::new(csv_string.as_bytes()).unwrap();
+        let (schema, records) = parser.parse_with_schema().unwrap();
+        let types = schema
+            .columns
+            .iter()
+            .map(|column| column.csv_type)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                CsvType::Int,
+                CsvType::Float,
+                CsvType::String,
+                CsvType::String
+            ]
+        );
+        assert_eq!(records[0]["score"], Value::Number(Number::Float(1.0)));
+        assert_eq!(records[0]["zip"], Value::String("02134".to_string()));
+        let score = schema.column("score").unwrap();
+        assert!(score.nullable);
+        assert_eq!(score.nulls, 1);
+        assert!(!schema.column("id").unwrap().nullable);
+
+        let options = CsvOptions::new().inference_sample(1);
+        let parser = CsvParser::with_options(csv_string.as_bytes(), &options).unwrap();
+        let (schema, records) = parser.parse_with_schema().unwrap();
+        assert_eq!(schema.column("score").unwrap().csv_type, CsvType::Int);
+        assert_eq!(schema.column("score").unwrap().mismatches, 1);
+        assert_eq!(records[1]["score"], Value::String("2.5".to_string()));
+        assert_eq!(
+            schema.to_string(),
+            "id: int\nscore: int, nullable (1 nulls), 1 values did not fit\nzip: string\nlabel: string, nullable (1 nulls)\n"
+        );
+    }
+
+    #[test]
+    fn infer_long_integers_as_strings() {
+        let parser = CsvParser::new(b"acct,n\n12345678901234567890,1\n1,2.5\n").unwrap();
+        let (schema, records) = parser.parse_with_schema().unwrap();
+        assert_eq!(schema.column("acct").unwrap().csv_type, CsvType::String);
+        assert_eq!(
+            records[0]["acct"],
+            Value::String("12345678901234567890".to_string())
+        );
+        assert_eq!(schema.column("n").unwrap().csv_type, CsvType::Float);
+    }
+
+    #[test]
+    fn read_null_and_boolean_words() {
+        let csv_string = "id,active,score,note\n1,Yes,NA,\"\"\n2,N,-,\n3,Y,4,NA\n";
+        let options = CsvOptions::new()
+            .null_values(["NA"])
+            .column_null_values("score", ["-", "NA"])
+            .column_null_values("note", ["-"])
+            .column_bool_values("active", ["Yes", "Y"], ["No", "N"]);
+        let parser = CsvParser::with_options(csv_string.as_bytes(), &options).unwrap();
+        let records = parser.parse_records().unwrap();
+        let column = |name: &str| {
+            records
+                .iter()
+                .map(|record| record[name].clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            column("active"),
+            vec![
+                Value::Boolean(true),
+                Value::Boolean(false),
+                Value::Boolean(true)
+            ]
+        );
+        assert_eq!(
+            column("score"),
+            vec![Value::Null, Value::Null, Value::Number(Number::Int(4))]
+        );
+        assert_eq!(
+            column("note"),
+            vec![
+                Value::String(String::new()),
+                Value::Null,
+                Value::String("NA".to_string())
+            ]
+        );
+        let (schema, _) = parser.parse_with_schema().unwrap();
+        assert_eq!(schema.column("active").unwrap().csv_type, CsvType::Bool);
+        assert_eq!(schema.column("score").unwrap().csv_type, CsvType::Int);
+
+        let options = options.quoted_empty_is_null(true);
+        let parser = CsvParser::with_options(csv_string.as_bytes(), &options).unwrap();
+        assert_eq!(parser.parse_records().unwrap()[0]["note"], Value::Null);
+
+        let options = CsvOptions::new().column_null_values("missing", ["NA"]);
+        let error = CsvParser::with_options(csv_string.as_bytes(), &options).err();
+        assert_eq!(
+            error.unwrap().message,
+            "null values for unknown column \"missing\""
+        );
+    }
 }
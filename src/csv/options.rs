@@ -0,0 +1,332 @@
+use std::borrow::Cow;
+
+use crate::{encoding, DateFormat, ParsingError, ParsonResult};
+
+use super::{CsvDuplicateHeaders, CsvType};
+
+/// How the bytes handed to a [`CsvParser`](super::CsvParser) are decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CsvEncoding {
+    /// UTF-8, UTF-16 or UTF-32, told apart by their byte order mark.
+    #[default]
+    Auto,
+    /// ISO-8859-1.
+    Latin1,
+    Windows1252,
+    /// ISO-8859-15, Latin-1 with the euro sign.
+    Iso8859_15,
+    /// UTF-8 where invalid bytes are replaced with U+FFFD instead of failing;
+    /// the affected lines are listed by [`CsvParser::lossy_lines`](super::CsvParser::lossy_lines).
+    Utf8Lossy,
+}
+
+impl CsvEncoding {
+    /// Turns `bytes` into UTF-8, along with the lines that needed replacements.
+    pub(crate) fn decode<'a>(&self, bytes: &'a [u8]) -> ParsonResult<(Cow<'a, [u8]>, Vec<usize>)> {
+        let decoded = match self {
+            CsvEncoding::Auto => return Ok((encoding::to_utf8(bytes)?, vec![])),
+            CsvEncoding::Latin1 => encoding::decode_single_byte(bytes, encoding::latin1),
+            CsvEncoding::Windows1252 => encoding::decode_single_byte(bytes, encoding::windows_1252),
+            CsvEncoding::Iso8859_15 => encoding::decode_single_byte(bytes, encoding::iso_8859_15),
+            CsvEncoding::Utf8Lossy => {
+                let (decoded, offsets) = encoding::decode_lossy(bytes);
+                return Ok((Cow::Owned(decoded.into_bytes()), offsets));
+            }
+        };
+        Ok((Cow::Owned(decoded.into_bytes()), vec![]))
+    }
+}
+
+/// How a quote or other special character is written inside a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CsvEscape {
+    /// A quote inside a quoted field is written twice, as in RFC 4180.
+    #[default]
+    Doubled,
+    /// A backslash makes the next character literal, in and out of quotes;
+    /// `\n`, `\r`, `\t` and `\0` stand for the control characters.
+    Backslash,
+}
+
+/// What ends a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CsvTerminator {
+    /// `\r\n`, `\n` or a lone `\r`, so files with Windows, unix and old Mac
+    /// line endings, or a mix of them, all read the same.
+    #[default]
+    Lines,
+    Char(char),
+}
+
+/// The punctuation of a csv file. The default is RFC 4180 csv, which also
+/// reads files with unix and old Mac line endings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvDialect {
+    pub(crate) delimiter: String,
+    pub(crate) quote: Option<char>,
+    pub(crate) escape: CsvEscape,
+    pub(crate) terminator: CsvTerminator,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: ",".to_string(),
+            quote: Some('"'),
+            escape: CsvEscape::Doubled,
+            terminator: CsvTerminator::Lines,
+        }
+    }
+}
+
+impl CsvDialect {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// What Excel writes: commas and doubled quotes. Its CRLF line endings
+    /// are read by [`CsvTerminator::Lines`], so this is the same as the
+    /// default.
+    pub fn excel() -> Self {
+        Self::default()
+    }
+
+    /// Tab separated values, without quoting; tabs, newlines and backslashes
+    /// in a value are written `\t`, `\n` and `\\`.
+    pub fn tsv() -> Self {
+        Self::default()
+            .delimiter("\t")
+            .quote(None)
+            .escape(CsvEscape::Backslash)
+    }
+
+    /// Commas, backslash escapes and `\n` line endings, as written by many
+    /// database and unix tools.
+    pub fn unix() -> Self {
+        Self::default()
+            .escape(CsvEscape::Backslash)
+            .terminator(CsvTerminator::Char('\n'))
+    }
+
+    /// The field separator, which may be several characters long, e.g. `"||"`.
+    pub fn delimiter(mut self, delimiter: impl Into<String>) -> Self {
+        self.delimiter = delimiter.into();
+        self
+    }
+
+    /// The character around quoted fields, `None` when fields are never quoted.
+    pub fn quote(mut self, quote: Option<char>) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    pub fn escape(mut self, escape: CsvEscape) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    pub fn terminator(mut self, terminator: CsvTerminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    pub(crate) fn validate(&self) -> ParsonResult<()> {
+        let invalid = |message: &str| {
+            Err(ParsingError {
+                message: format!("invalid csv dialect: {}", message),
+            })
+        };
+        let terminator = match self.terminator {
+            CsvTerminator::Lines => '\n',
+            CsvTerminator::Char(terminator) => terminator,
+        };
+        if self.delimiter.is_empty() {
+            return invalid("the delimiter is empty");
+        }
+        if self
+            .quote
+            .is_some_and(|quote| self.delimiter.contains(quote))
+        {
+            return invalid("the delimiter contains the quote character");
+        }
+        if self.delimiter.contains(terminator) {
+            return invalid("the delimiter contains the record terminator");
+        }
+        if self.escape == CsvEscape::Backslash && self.delimiter.contains('\\') {
+            return invalid("the delimiter contains the escape character");
+        }
+        Ok(())
+    }
+}
+
+/// Where the column names come from.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum CsvHeader {
+    /// The first record, after any skipped lines, holds the column names.
+    #[default]
+    FirstRow,
+    /// Every record is data; columns are named `column_1`, `column_2`, ...
+    None,
+    /// Every record is data, in columns with the given names.
+    Names(Vec<String>),
+}
+
+/// The words for true, then those for false.
+pub(crate) type BoolWords = (Vec<String>, Vec<String>);
+
+/// Settings for [`CsvParser::with_options`](super::CsvParser::with_options).
+#[derive(Debug, Clone, Default)]
+pub struct CsvOptions {
+    pub(crate) encoding: CsvEncoding,
+    pub(crate) dialect: CsvDialect,
+    pub(crate) header: CsvHeader,
+    pub(crate) skip_lines: usize,
+    pub(crate) duplicate_headers: CsvDuplicateHeaders,
+    pub(crate) column_types: Vec<(String, CsvType)>,
+    pub(crate) all_strings: bool,
+    pub(crate) infer_quoted: bool,
+    pub(crate) inference_sample: Option<usize>,
+    pub(crate) date_formats: Option<Vec<DateFormat>>,
+    pub(crate) column_formats: Vec<(String, DateFormat)>,
+    pub(crate) null_values: Vec<String>,
+    pub(crate) column_null_values: Vec<(String, Vec<String>)>,
+    pub(crate) bool_values: Option<BoolWords>,
+    pub(crate) column_bool_values: Vec<(String, BoolWords)>,
+    pub(crate) quoted_empty_is_null: bool,
+}
+
+impl CsvOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn encoding(mut self, encoding: CsvEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn dialect(mut self, dialect: CsvDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    pub fn header(mut self, header: CsvHeader) -> Self {
+        self.header = header;
+        self
+    }
+
+    pub fn duplicate_headers(mut self, policy: CsvDuplicateHeaders) -> Self {
+        self.duplicate_headers = policy;
+        self
+    }
+
+    /// Reads the column named `column` as `csv_type`; a field that is not of
+    /// that type fails the parse. Empty fields are null whatever the type.
+    pub fn column_type(mut self, column: impl Into<String>, csv_type: CsvType) -> Self {
+        self.column_types.push((column.into(), csv_type));
+        self
+    }
+
+    /// Reads the columns without a type hint as strings rather than guessing
+    /// numbers and booleans. Empty fields are still null.
+    pub fn all_strings(mut self, all_strings: bool) -> Self {
+        self.all_strings = all_strings;
+        self
+    }
+
+    /// Also guesses numbers in quoted fields, which are otherwise strings.
+    pub fn infer_quoted(mut self, infer_quoted: bool) -> Self {
+        self.infer_quoted = infer_quoted;
+        self
+    }
+
+    /// Reads the column named `column` as the dates or times `format` gives,
+    /// like [`CsvOptions::column_type`] with [`CsvType::Date`],
+    /// [`CsvType::Time`] or [`CsvType::DateTime`].
+    pub fn column_format(mut self, column: impl Into<String>, format: DateFormat) -> Self {
+        let column = column.into();
+        self.column_types
+            .push((column.clone(), CsvType::from(format.kind())));
+        self.column_formats.push((column, format));
+        self
+    }
+
+    /// The formats dates and times are read and inferred with, in order of
+    /// preference, instead of [`DateFormat::defaults`].
+    pub fn date_formats(mut self, formats: Vec<DateFormat>) -> Self {
+        self.date_formats = Some(formats);
+        self
+    }
+
+    /// Reads fields that are exactly one of `values`, quoted or not, as null,
+    /// e.g. `NULL`, `NA` or `\N`. Empty fields are null in any case.
+    pub fn null_values<S: Into<String>>(mut self, values: impl IntoIterator<Item = S>) -> Self {
+        self.null_values = values.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Like [`CsvOptions::null_values`], for the column named `column` only.
+    pub fn column_null_values<S: Into<String>>(
+        mut self,
+        column: impl Into<String>,
+        values: impl IntoIterator<Item = S>,
+    ) -> Self {
+        let values = values.into_iter().map(Into::into).collect();
+        self.column_null_values.push((column.into(), values));
+        self
+    }
+
+    /// Reads fields that are exactly one of `trues` or `falses` as booleans,
+    /// instead of `true` and `false`, e.g. `Yes` and `No` or `1` and `0`.
+    pub fn bool_values<S: Into<String>>(
+        mut self,
+        trues: impl IntoIterator<Item = S>,
+        falses: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.bool_values = Some(words(trues, falses));
+        self
+    }
+
+    /// Like [`CsvOptions::bool_values`], for the column named `column` only.
+    pub fn column_bool_values<S: Into<String>>(
+        mut self,
+        column: impl Into<String>,
+        trues: impl IntoIterator<Item = S>,
+        falses: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.column_bool_values
+            .push((column.into(), words(trues, falses)));
+        self
+    }
+
+    /// Reads a quoted empty field, `""`, as null like an unquoted one rather
+    /// than as an empty string.
+    pub fn quoted_empty_is_null(mut self, quoted_empty_is_null: bool) -> Self {
+        self.quoted_empty_is_null = quoted_empty_is_null;
+        self
+    }
+
+    /// Infers column types in [`CsvParser::parse_with_schema`](super::CsvParser::parse_with_schema)
+    /// from the first `rows` records only, instead of all of them.
+    pub fn inference_sample(mut self, rows: usize) -> Self {
+        self.inference_sample = Some(rows);
+        self
+    }
+
+    /// Skips the first `count` lines, such as a title or export notes above
+    /// the header. They are skipped as plain lines, whatever quotes they hold.
+    pub fn skip_lines(mut self, count: usize) -> Self {
+        self.skip_lines = count;
+        self
+    }
+}
+
+fn words<S: Into<String>>(
+    trues: impl IntoIterator<Item = S>,
+    falses: impl IntoIterator<Item = S>,
+) -> BoolWords {
+    (
+        trues.into_iter().map(Into::into).collect(),
+        falses.into_iter().map(Into::into).collect(),
+    )
+}
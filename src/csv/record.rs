@@ -0,0 +1,198 @@
+use std::{collections::HashMap, ops::Index, sync::Arc};
+
+use crate::{ParsingError, ParsonResult};
+
+use super::Value;
+
+/// What to do with a column name that appears more than once in the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CsvDuplicateHeaders {
+    /// Fail to parse the file.
+    Error,
+    /// Rename the later columns `name_2`, `name_3`, ...
+    #[default]
+    Suffix,
+    /// Keep the names as they are; lookups by name find the first column and
+    /// [`CsvRecord::get_all`] finds them all.
+    KeepAll,
+}
+
+impl CsvDuplicateHeaders {
+    pub(crate) fn apply(&self, names: Vec<String>) -> ParsonResult<Vec<String>> {
+        let mut seen = HashMap::<String, usize>::new();
+        let mut unique = Vec::with_capacity(names.len());
+        for name in &names {
+            *seen.entry(name.clone()).or_default() += 1;
+        }
+        let mut counts = HashMap::<&str, usize>::new();
+        for name in &names {
+            let count = counts.entry(name).or_default();
+            *count += 1;
+            if *count == 1 || *self == CsvDuplicateHeaders::KeepAll {
+                unique.push(name.clone());
+                continue;
+            }
+            if *self == CsvDuplicateHeaders::Error {
+                return Err(ParsingError {
+                    message: format!("column {:?} appears more than once in the header", name),
+                });
+            }
+            // skip suffixes that are column names already
+            let mut suffix = *count;
+            while seen.contains_key(&format!("{}_{}", name, suffix)) {
+                suffix += 1;
+            }
+            let renamed = format!("{}_{}", name, suffix);
+            seen.insert(renamed.clone(), 1);
+            unique.push(renamed);
+        }
+        Ok(unique)
+    }
+}
+
+/// The column names of a file, shared by all of its records.
+#[derive(Debug)]
+pub(crate) struct Header {
+    pub(crate) names: Vec<String>,
+    index: HashMap<String, usize>,
+}
+
+impl Header {
+    pub(crate) fn new(names: Vec<String>) -> Self {
+        let mut index = HashMap::new();
+        for (idx, name) in names.iter().enumerate() {
+            index.entry(name.clone()).or_insert(idx);
+        }
+        Self { names, index }
+    }
+}
+
+/// A csv record: its values in column order, and the header of the file.
+#[derive(Debug)]
+pub struct CsvRecord {
+    header: Arc<Header>,
+    values: Vec<Value>,
+}
+
+impl CsvRecord {
+    pub(crate) fn new(header: Arc<Header>, values: Vec<Value>) -> Self {
+        Self { header, values }
+    }
+
+    /// The value in the first column named `name`.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(*self.header.index.get(name)?)
+    }
+
+    /// The values in every column named `name`, for headers with duplicates.
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Value> + 'a {
+        self.iter()
+            .filter(move |(column, _)| *column == name)
+            .map(|(_, value)| value)
+    }
+
+    /// The value in column `idx`, counting from 0.
+    pub fn at(&self, idx: usize) -> Option<&Value> {
+        self.values.get(idx)
+    }
+
+    /// The column names and values, in column order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.header
+            .names
+            .iter()
+            .map(String::as_str)
+            .zip(&self.values)
+    }
+
+    pub fn header(&self) -> &[String] {
+        &self.header.names
+    }
+
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    pub fn into_values(self) -> Vec<Value> {
+        self.values
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl Index<usize> for CsvRecord {
+    type Output = Value;
+
+    fn index(&self, idx: usize) -> &Value {
+        &self.values[idx]
+    }
+}
+
+impl Index<&str> for CsvRecord {
+    type Output = Value;
+
+    fn index(&self, name: &str) -> &Value {
+        self.get(name)
+            .unwrap_or_else(|| panic!("no column named {:?}", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::csv::{Number, Value};
+
+    use super::{CsvDuplicateHeaders, CsvRecord, Header};
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn rename_or_reject_duplicate_names() {
+        let header = names(&["a", "b", "a", "a_2", "a"]);
+        assert_eq!(
+            CsvDuplicateHeaders::Suffix.apply(header.clone()).unwrap(),
+            names(&["a", "b", "a_3", "a_2", "a_4"])
+        );
+        assert_eq!(
+            CsvDuplicateHeaders::KeepAll.apply(header.clone()).unwrap(),
+            header
+        );
+        let error = CsvDuplicateHeaders::Error.apply(header).unwrap_err();
+        assert_eq!(
+            error.message,
+            "column \"a\" appears more than once in the header"
+        );
+    }
+
+    #[test]
+    fn look_up_values_by_name_and_position() {
+        let header = Arc::new(Header::new(names(&["id", "tag", "tag"])));
+        let record = CsvRecord::new(
+            header,
+            vec![
+                Value::Number(Number::Int(7)),
+                Value::String("x".to_string()),
+                Value::Null,
+            ],
+        );
+        assert!(matches!(record["id"], Value::Number(Number::Int(7))));
+        assert!(matches!(record.get("tag"), Some(Value::String(tag)) if tag == "x"));
+        assert_eq!(record.get_all("tag").count(), 2);
+        assert!(matches!(record[2], Value::Null));
+        assert!(record.at(3).is_none());
+        assert!(record.get("missing").is_none());
+        assert_eq!(
+            record.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            vec!["id", "tag", "tag"]
+        );
+    }
+}
@@ -0,0 +1,547 @@
+use std::fmt::Display;
+
+use crate::{
+    datetime::{DateFormat, Kind},
+    ParsingError, ParsonResult, Temporal,
+};
+
+use super::{
+    token::{has_leading_zero, is_long_integer, Field, Number},
+    words::Words,
+    CsvOptions, Value,
+};
+
+/// The type a column is read as, instead of guessing it field by field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvType {
+    String,
+    Int,
+    Float,
+    /// `true` or `false`, in any case.
+    Bool,
+    /// A date, in one of the [`DateFormat`]s of the column.
+    Date,
+    /// A time of day, in one of the [`DateFormat`]s of the column.
+    Time,
+    /// A date and time, in one of the [`DateFormat`]s of the column.
+    DateTime,
+    /// A decimal number kept digit for digit, as [`Number::Decimal`].
+    Decimal,
+}
+
+impl Display for CsvType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            CsvType::String => "string",
+            CsvType::Int => "int",
+            CsvType::Float => "float",
+            CsvType::Bool => "bool",
+            CsvType::Date => "date",
+            CsvType::Time => "time",
+            CsvType::DateTime => "datetime",
+            CsvType::Decimal => "decimal",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl CsvType {
+    /// Reads `text` as this type, `None` if it is not one. Dates and times
+    /// are read with the first of `formats` that fits.
+    fn read(&self, text: &str, formats: &[DateFormat]) -> Option<Value> {
+        let trimmed = text.trim();
+        let value = match self {
+            CsvType::String => Value::String(text.to_string()),
+            CsvType::Int => Value::Number(Number::Int(trimmed.parse().ok()?)),
+            CsvType::Float => Value::Number(Number::Float(trimmed.parse().ok()?)),
+            CsvType::Bool if trimmed.eq_ignore_ascii_case("true") => Value::Boolean(true),
+            CsvType::Bool if trimmed.eq_ignore_ascii_case("false") => Value::Boolean(false),
+            CsvType::Bool => return None,
+            CsvType::Date | CsvType::Time | CsvType::DateTime => formats
+                .iter()
+                .filter(|format| CsvType::from(format.kind()) == *self)
+                .find_map(|format| format.parse(trimmed))?
+                .into(),
+            CsvType::Decimal => Value::Number(Number::Decimal(decimal(trimmed)?)),
+        };
+        Some(value)
+    }
+}
+
+impl From<Kind> for CsvType {
+    fn from(kind: Kind) -> Self {
+        match kind {
+            Kind::Date => CsvType::Date,
+            Kind::Time => CsvType::Time,
+            Kind::DateTime => CsvType::DateTime,
+        }
+    }
+}
+
+impl From<Temporal> for Value {
+    fn from(temporal: Temporal) -> Self {
+        match temporal {
+            Temporal::Date(date) => Value::Date(date),
+            Temporal::Time(time) => Value::Time(time),
+            Temporal::DateTime(date_time) => Value::DateTime(date_time),
+        }
+    }
+}
+
+/// Checks `text` is a plain decimal number, returning it without a `+` sign.
+fn decimal(text: &str) -> Option<String> {
+    let unsigned = text.strip_prefix(['-', '+']).unwrap_or(text);
+    let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let is_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+    if whole.is_empty() && fraction.is_empty() || !is_digits(whole) || !is_digits(fraction) {
+        return None;
+    }
+    Some(text.strip_prefix('+').unwrap_or(text).to_string())
+}
+
+/// A column as found by [`CsvParser::parse_with_schema`](super::CsvParser::parse_with_schema).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvColumn {
+    pub name: String,
+    pub csv_type: CsvType,
+    /// The format dates and times of the column were found in.
+    pub format: Option<DateFormat>,
+    /// Whether the column has empty fields, read as null.
+    pub nullable: bool,
+    pub nulls: usize,
+    /// Values that are not of the column type, kept as strings. Only rows
+    /// outside the inference sample can have some.
+    pub mismatches: usize,
+}
+
+/// The type of every column of a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvSchema {
+    pub columns: Vec<CsvColumn>,
+}
+
+impl CsvSchema {
+    pub fn column(&self, name: &str) -> Option<&CsvColumn> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+}
+
+impl Display for CsvSchema {
+    /// Writes one line per column, e.g. `price: float, nullable (2 nulls)`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for column in &self.columns {
+            write!(f, "{}: {}", column.name, column.csv_type)?;
+            if let Some(format) = &column.format {
+                write!(f, " ({})", format)?;
+            }
+            if column.nullable {
+                write!(f, ", nullable ({} nulls)", column.nulls)?;
+            }
+            if column.mismatches > 0 {
+                write!(f, ", {} values did not fit", column.mismatches)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Turns the fields of a file into values, following the type hints and
+/// inference settings of its [`CsvOptions`].
+#[derive(Debug)]
+pub(crate) struct Typing {
+    types: Vec<Option<CsvType>>,
+    formats: Vec<Option<DateFormat>>,
+    date_formats: Vec<DateFormat>,
+    words: Vec<Words>,
+    default_words: Words,
+    all_strings: bool,
+    infer_quoted: bool,
+    sample: Option<usize>,
+}
+
+impl Typing {
+    pub(crate) fn new(options: &CsvOptions, header: &[String]) -> ParsonResult<Self> {
+        let types = by_column(header, &options.column_types, "type hint")?;
+        let formats = by_column(header, &options.column_formats, "type hint")?;
+        let nulls = by_column(header, &options.column_null_values, "null values")?;
+        let bools = by_column(header, &options.column_bool_values, "boolean values")?;
+        let default_words = Words::new(options);
+        let words = nulls
+            .into_iter()
+            .zip(bools)
+            .map(|(nulls, bools)| default_words.with(nulls, bools))
+            .collect();
+        Ok(Self {
+            types,
+            formats,
+            words,
+            default_words,
+            date_formats: options
+                .date_formats
+                .clone()
+                .unwrap_or_else(DateFormat::defaults),
+            all_strings: options.all_strings,
+            infer_quoted: options.infer_quoted,
+            sample: options.inference_sample,
+        })
+    }
+
+    pub(crate) fn hint(&self, idx: usize) -> Option<CsvType> {
+        self.types.get(idx).copied().flatten()
+    }
+
+    /// The null and boolean words of column `idx`.
+    pub(crate) fn words(&self, idx: usize) -> &Words {
+        self.words.get(idx).unwrap_or(&self.default_words)
+    }
+
+    /// The formats dates and times of column `idx` are read with.
+    fn date_formats(&self, idx: usize) -> &[DateFormat] {
+        match self.formats.get(idx) {
+            Some(Some(format)) => std::slice::from_ref(format),
+            _ => &self.date_formats,
+        }
+    }
+
+    /// Decides one type per column: the hint if there is one, otherwise the
+    /// narrowest of bool, int and float that fits every non-null field of
+    /// the sampled rows, then the first date format that does, then string.
+    /// Columns of dates or times come with the format they are in.
+    pub(crate) fn infer(
+        &self,
+        rows: &[Vec<Field>],
+        columns: usize,
+    ) -> Vec<(CsvType, Option<DateFormat>)> {
+        let sample = &rows[..rows.len().min(self.sample.unwrap_or(usize::MAX))];
+        (0..columns)
+            .map(|idx| {
+                if let Some(csv_type) = self.hint(idx) {
+                    return (csv_type, self.formats.get(idx).cloned().flatten());
+                }
+                if self.all_strings {
+                    return (CsvType::String, None);
+                }
+                let fields = sample
+                    .iter()
+                    .filter_map(|row| row.get(idx))
+                    .filter(|field| !self.words(idx).is_null(field))
+                    .collect::<Vec<_>>();
+                if fields.is_empty() {
+                    return (CsvType::String, None);
+                }
+                let fits = |csv_type, formats: &[DateFormat]| {
+                    fields
+                        .iter()
+                        .all(|field| self.fits(idx, csv_type, formats, field))
+                };
+                if let Some(csv_type) = [CsvType::Bool, CsvType::Int, CsvType::Float]
+                    .into_iter()
+                    .find(|csv_type| fits(*csv_type, &[]))
+                {
+                    return (csv_type, None);
+                }
+                self.date_formats(idx)
+                    .iter()
+                    .find(|format| fits(format.kind().into(), std::slice::from_ref(format)))
+                    .map(|format| (format.kind().into(), Some(format.clone())))
+                    .unwrap_or((CsvType::String, None))
+            })
+            .collect()
+    }
+
+    /// Reads a non-empty `field` as `csv_type` if it is one, following the
+    /// rules of the per field inference: quoted fields and zero-padded
+    /// numbers are not numbers.
+    pub(crate) fn read_as(
+        &self,
+        idx: usize,
+        csv_type: CsvType,
+        formats: &[DateFormat],
+        field: &Field,
+    ) -> Option<Value> {
+        self.fits(idx, csv_type, formats, field)
+            .then(|| self.read(idx, csv_type, formats, &field.value))
+            .flatten()
+    }
+
+    fn fits(&self, idx: usize, csv_type: CsvType, formats: &[DateFormat], field: &Field) -> bool {
+        let text = &field.value;
+        match csv_type {
+            CsvType::Bool => self.read(idx, csv_type, formats, text).is_some(),
+            CsvType::Int | CsvType::Float | CsvType::Decimal
+                if field.quoted && !self.infer_quoted =>
+            {
+                false
+            }
+            // integers too long for an i64 are kept as text, not rounded
+            CsvType::Float if is_long_integer(text) => false,
+            CsvType::Int | CsvType::Float | CsvType::Decimal => {
+                text.starts_with(|char| matches!(char, '0'..='9' | '-' | '+'))
+                    && !has_leading_zero(text)
+                    && csv_type.read(text, formats).is_some()
+            }
+            _ => csv_type.read(text, formats).is_some(),
+        }
+    }
+
+    /// Reads `text` as `csv_type`, with the boolean words of column `idx` as
+    /// well as `true` and `false` in any case.
+    fn read(
+        &self,
+        idx: usize,
+        csv_type: CsvType,
+        formats: &[DateFormat],
+        text: &str,
+    ) -> Option<Value> {
+        match self.words(idx).boolean(text) {
+            Some(boolean) if csv_type == CsvType::Bool => Some(Value::Boolean(boolean)),
+            _ => csv_type.read(text, formats),
+        }
+    }
+
+    /// The value of `field`, in column `idx` named `column` of the record
+    /// starting on `line`.
+    pub(crate) fn value(
+        &self,
+        field: &Field,
+        idx: usize,
+        column: &str,
+        line: usize,
+    ) -> ParsonResult<Value> {
+        let words = self.words(idx);
+        let csv_type = match self.types.get(idx).copied().flatten() {
+            Some(csv_type) => csv_type,
+            None if self.all_strings => CsvType::String,
+            None if self.infer_quoted && field.quoted && !field.value.is_empty() => {
+                let unquoted = Field {
+                    value: field.value.clone(),
+                    quoted: false,
+                };
+                return Ok(unquoted.to_token(words)?.into());
+            }
+            None => return Ok(field.to_token(words)?.into()),
+        };
+        if words.is_null(field) || field.value.is_empty() && csv_type != CsvType::String {
+            return Ok(Value::Null);
+        }
+        self.read(idx, csv_type, self.date_formats(idx), &field.value)
+            .ok_or_else(|| ParsingError {
+                message: format!(
+                    "line {}, column {:?}: cannot read {:?} as {}",
+                    line, column, field.value, csv_type
+                ),
+            })
+    }
+}
+
+impl Default for Typing {
+    fn default() -> Self {
+        Self::new(&CsvOptions::default(), &[]).expect("there are no hints to check")
+    }
+}
+
+/// Spreads settings given by column name over the columns of `header`.
+fn by_column<T: Clone>(
+    header: &[String],
+    settings: &[(String, T)],
+    what: &str,
+) -> ParsonResult<Vec<Option<T>>> {
+    let mut by_column = vec![None; header.len()];
+    for (name, setting) in settings {
+        let mut found = false;
+        for (idx, column) in header.iter().enumerate() {
+            if column == name {
+                by_column[idx] = Some(setting.clone());
+                found = true;
+            }
+        }
+        if !found {
+            return Err(ParsingError {
+                message: format!("{} for unknown column {:?}", what, name),
+            });
+        }
+    }
+    Ok(by_column)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        csv::{token::Field, CsvOptions, Number, Value},
+        Date, DateFormat, DateTime, Time,
+    };
+
+    use super::{CsvType, Typing};
+
+    fn unquoted(value: &str) -> Field {
+        Field {
+            value: value.to_string(),
+            quoted: false,
+        }
+    }
+
+    fn quoted(value: &str) -> Field {
+        Field {
+            value: value.to_string(),
+            quoted: true,
+        }
+    }
+
+    #[test]
+    fn read_hinted_types() {
+        let cases = [
+            (CsvType::String, "0012", Value::String("0012".to_string())),
+            (
+                CsvType::Int,
+                "3588358312619246",
+                Value::Number(Number::Int(3588358312619246)),
+            ),
+            (CsvType::Float, " 2.5", Value::Number(Number::Float(2.5))),
+            (CsvType::Bool, "TRUE", Value::Boolean(true)),
+            (
+                CsvType::Date,
+                "2021-05-22",
+                Value::Date(Date::new(2021, 5, 22).unwrap()),
+            ),
+            (
+                CsvType::Date,
+                "5/22/2021",
+                Value::Date(Date::new(2021, 5, 22).unwrap()),
+            ),
+            (
+                CsvType::Time,
+                "8:05 PM",
+                Value::Time(Time::new(20, 5, 0, 0).unwrap()),
+            ),
+            (
+                CsvType::DateTime,
+                "2021-05-22T20:05:00Z",
+                Value::DateTime(
+                    DateTime::new(
+                        Date::new(2021, 5, 22).unwrap(),
+                        Time::new(20, 5, 0, 0).unwrap(),
+                        Some(0),
+                    )
+                    .unwrap(),
+                ),
+            ),
+            (
+                CsvType::Decimal,
+                "+0012.50",
+                Value::Number(Number::Decimal("0012.50".to_string())),
+            ),
+        ];
+        let formats = DateFormat::defaults();
+        for (csv_type, text, expected) in cases {
+            assert_eq!(csv_type.read(text, &formats), Some(expected));
+        }
+        for (csv_type, text) in [
+            (CsvType::Int, "1.5"),
+            (CsvType::Bool, "yes"),
+            (CsvType::Date, "22/5/2021"),
+            (CsvType::Date, "20:05"),
+            (CsvType::Time, "2021-05-22"),
+            (CsvType::Decimal, "1e3"),
+            (CsvType::Decimal, "."),
+        ] {
+            assert_eq!(csv_type.read(text, &formats), None, "{} {}", csv_type, text);
+        }
+    }
+
+    #[test]
+    fn apply_hints_and_inference_settings() {
+        let header = ["id".to_string(), "zip".to_string()];
+        let options = CsvOptions::new().column_type("zip", CsvType::String);
+        let typing = Typing::new(&options, &header).unwrap();
+        assert_eq!(
+            typing.value(&unquoted("02134"), 1, "zip", 2).unwrap(),
+            Value::String("02134".to_string())
+        );
+        assert_eq!(
+            typing.value(&unquoted("7"), 0, "id", 2).unwrap(),
+            Value::Number(Number::Int(7))
+        );
+        assert_eq!(
+            typing.value(&quoted("7"), 0, "id", 2).unwrap(),
+            Value::String("7".to_string())
+        );
+
+        let options = CsvOptions::new()
+            .column_type("id", CsvType::Int)
+            .infer_quoted(true);
+        let typing = Typing::new(&options, &header).unwrap();
+        assert_eq!(typing.value(&quoted(""), 0, "id", 2).unwrap(), Value::Null);
+        assert_eq!(
+            typing.value(&quoted("7"), 1, "zip", 2).unwrap(),
+            Value::Number(Number::Int(7))
+        );
+        let error = typing.value(&unquoted("x7"), 0, "id", 3).unwrap_err();
+        assert_eq!(
+            error.message,
+            "line 3, column \"id\": cannot read \"x7\" as int"
+        );
+
+        let typing = Typing::new(&CsvOptions::new().all_strings(true), &header).unwrap();
+        assert_eq!(
+            typing.value(&unquoted("7"), 0, "id", 2).unwrap(),
+            Value::String("7".to_string())
+        );
+        assert_eq!(
+            typing.value(&unquoted(""), 0, "id", 2).unwrap(),
+            Value::Null
+        );
+
+        let options = CsvOptions::new().column_type("missing", CsvType::Int);
+        assert!(Typing::new(&options, &header).is_err());
+    }
+
+    #[test]
+    fn infer_one_type_per_column() {
+        let rows = [
+            ["1", "1", "007", "true", "1/2/2024", "", "x"],
+            ["2", "2.5", "12", "false", "12/31/2024", "", "1"],
+            ["3", "", "3", "", "", "", "\"2\""],
+        ]
+        .map(|row| {
+            row.map(|value| match value.strip_prefix('"') {
+                Some(value) => quoted(value.trim_end_matches('"')),
+                None => unquoted(value),
+            })
+            .to_vec()
+        });
+        let typing = Typing::default();
+        let inferred = typing.infer(&rows, 7);
+        assert_eq!(inferred[4].1, Some(DateFormat::us_date()));
+        assert_eq!(
+            inferred
+                .into_iter()
+                .map(|(csv_type, _)| csv_type)
+                .collect::<Vec<_>>(),
+            vec![
+                CsvType::Int,
+                CsvType::Float,
+                CsvType::String,
+                CsvType::Bool,
+                CsvType::Date,
+                CsvType::String,
+                CsvType::String,
+            ]
+        );
+
+        let options = CsvOptions::new()
+            .inference_sample(1)
+            .column_type("c", CsvType::Int);
+        let header = ["a", "b", "c"].map(String::from);
+        let typing = Typing::new(&options, &header).unwrap();
+        assert_eq!(
+            typing.infer(&rows, 3),
+            vec![
+                (CsvType::Int, None),
+                (CsvType::Int, None),
+                (CsvType::Int, None)
+            ]
+        );
+        assert_eq!(typing.read_as(0, CsvType::Int, &[], &unquoted("2.5")), None);
+    }
+}
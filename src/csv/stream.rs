@@ -0,0 +1,304 @@
+use std::sync::Arc;
+
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
+
+use crate::{
+    encoding::{self, Encoding},
+    ParsingError, ParsonResult,
+};
+
+use super::{
+    parse_values, read_header,
+    record::Header,
+    schema::Typing,
+    token::{RecordText, Tokenizer},
+    CsvEncoding, CsvOptions, CsvParser, CsvRecord,
+};
+
+/// Yields csv records as their lines are read, read with the same
+/// [`CsvOptions`] as a [`CsvParser`].
+///
+/// UTF-16 and UTF-32 input can't be split into lines before decoding, so
+/// [`CsvEncoding::Auto`] only reads UTF-8 here.
+pub struct CsvRecordStream<R> {
+    reader: BufReader<R>,
+    options: CsvOptions,
+    /// Text read but not parsed yet, starting at the next record.
+    buffer: String,
+    /// Whether anything was read yet, for the byte order mark.
+    started: bool,
+    /// Whether the lines of [`CsvOptions::skip_lines`] are behind.
+    skipped: bool,
+    header: Option<(Arc<Header>, Typing)>,
+    /// Lines parsed so far.
+    line: usize,
+}
+
+impl<R: AsyncRead + Unpin> CsvRecordStream<R> {
+    pub fn new(reader: R) -> Self {
+        Self::build(reader, CsvOptions::default())
+    }
+
+    pub fn with_options(reader: R, options: &CsvOptions) -> ParsonResult<Self> {
+        options.dialect.validate()?;
+        Ok(Self::build(reader, options.clone()))
+    }
+
+    fn build(reader: R, options: CsvOptions) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            options,
+            buffer: String::new(),
+            started: false,
+            skipped: false,
+            header: None,
+            line: 0,
+        }
+    }
+
+    pub async fn next_record(&mut self) -> Option<ParsonResult<CsvRecord>> {
+        loop {
+            let record = match self.read_record().await {
+                Ok(Some(record)) => record,
+                Ok(None) => return None,
+                Err(error) => {
+                    // what is left of the broken record can't be told apart
+                    self.buffer.clear();
+                    return Some(Err(error));
+                }
+            };
+            let line = self.line + 1;
+            self.buffer.drain(..record.len);
+            self.line += record.lines;
+
+            let line_length = self.header.as_ref().map(|(header, _)| header.names.len());
+            let fields = match Tokenizer::check_length(record.fields, line, line_length) {
+                Ok(fields) => fields,
+                Err(error) => return Some(Err(error)),
+            };
+            let (header, typing) = match &self.header {
+                Some(header) => header,
+                None => match read_header(&self.options, Some(&fields)) {
+                    Ok((header, typing, first_is_header)) => {
+                        let header = self.header.insert((Arc::new(header), typing));
+                        if first_is_header {
+                            continue;
+                        }
+                        header
+                    }
+                    Err(error) => return Some(Err(error)),
+                },
+            };
+            return Some(
+                parse_values(&header.names, typing, &fields, line)
+                    .map(|values| CsvRecord::new(header.clone(), values)),
+            );
+        }
+    }
+
+    /// Reads lines until the buffer starts with a whole record, after the
+    /// lines to skip. A quoted field may span several lines, and a record
+    /// that reaches the end of the buffer without a terminator may go on in
+    /// the next line.
+    async fn read_record(&mut self) -> ParsonResult<Option<RecordText>> {
+        let mut at_end = false;
+        while !self.skipped {
+            let skip = self.options.skip_lines;
+            match Tokenizer::skip_lines(&self.buffer, &self.options.dialect, skip) {
+                Some((len, lines)) => {
+                    self.buffer.drain(..len);
+                    self.line += lines;
+                    self.skipped = true;
+                }
+                None if at_end => return Ok(None),
+                None => at_end = !self.read_line().await?,
+            }
+        }
+        loop {
+            match Tokenizer::tokenize_record(&self.buffer, &self.options.dialect)? {
+                Some(record) if record.len == 0 && at_end => return Ok(None),
+                Some(record) if record.terminated || at_end => return Ok(Some(record)),
+                None if at_end => {
+                    return Err(ParsingError {
+                        message: "quoted field is never closed".to_string(),
+                    })
+                }
+                _ => {}
+            }
+            at_end = !self.read_line().await?;
+        }
+    }
+
+    /// Adds the next line of input to the buffer. Returns `false` at the end
+    /// of the input.
+    async fn read_line(&mut self) -> ParsonResult<bool> {
+        let mut bytes = vec![];
+        let read = self
+            .reader
+            .read_until(b'\n', &mut bytes)
+            .await
+            .map_err(|_| ParsingError {
+                message: "failed to tokenize buf".to_string(),
+            })?;
+        let text = self.decode(&bytes)?;
+        self.buffer.push_str(&text);
+        self.started = true;
+        Ok(read > 0)
+    }
+
+    /// Decodes a line of input. A `\n` byte is never part of a longer
+    /// character in the encodings read here, so lines decode on their own.
+    fn decode(&self, bytes: &[u8]) -> ParsonResult<String> {
+        let encoding = self.options.encoding;
+        let mut bytes = bytes;
+        if !self.started && matches!(encoding, CsvEncoding::Auto | CsvEncoding::Utf8Lossy) {
+            match encoding::detect(bytes) {
+                (Encoding::Utf8, bom) => bytes = &bytes[bom..],
+                _ if encoding == CsvEncoding::Auto => {
+                    return Err(ParsingError {
+                        message: "utf-16 and utf-32 csv can't be streamed".to_string(),
+                    })
+                }
+                _ => {}
+            }
+        }
+        let failed = |_| ParsingError {
+            message: "failed to tokenize buf".to_string(),
+        };
+        match encoding {
+            CsvEncoding::Auto => String::from_utf8(bytes.to_vec()).map_err(failed),
+            CsvEncoding::Utf8Lossy => Ok(String::from_utf8_lossy(bytes).into_owned()),
+            encoding => {
+                let (decoded, _) = encoding.decode(bytes)?;
+                String::from_utf8(decoded.into_owned()).map_err(failed)
+            }
+        }
+    }
+}
+
+impl CsvParser {
+    pub async fn new_async<R: AsyncRead + Unpin>(mut reader: R) -> ParsonResult<Self> {
+        let mut bytes = vec![];
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|_| ParsingError {
+                message: "failed to tokenize buf".to_string(),
+            })?;
+        Self::new(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::csv::{
+        token::Number, CsvDialect, CsvEncoding, CsvHeader, CsvOptions, CsvParser, CsvRecord,
+        CsvType, Value,
+    };
+
+    use super::CsvRecordStream;
+
+    fn columns(records: &[CsvRecord]) -> Vec<Vec<(String, Value)>> {
+        records
+            .iter()
+            .map(|record| {
+                record
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.clone()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Streams `bytes`, checking the records match what [`CsvParser`] gives.
+    async fn stream_like_the_parser(bytes: &[u8], options: &CsvOptions) -> Vec<CsvRecord> {
+        let mut stream = CsvRecordStream::with_options(bytes, options).unwrap();
+        let mut streamed = vec![];
+        while let Some(record) = stream.next_record().await {
+            streamed.push(record.unwrap());
+        }
+        let parsed = CsvParser::with_options(bytes, options)
+            .unwrap()
+            .parse_records()
+            .unwrap();
+        assert_eq!(columns(&streamed), columns(&parsed));
+        streamed
+    }
+
+    #[tokio::test]
+    async fn parse_from_async_reader() {
+        let parser = CsvParser::new_async(&b"a,b\n1,x\n2,y"[..]).await.unwrap();
+        assert_eq!(parser.parse().unwrap().len(), 2);
+
+        assert!(CsvParser::new_async(&b"a,b\n1,x,3"[..]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn stream_records() {
+        let mut stream = CsvRecordStream::new(&b"a,b\n1,x\n2,y"[..]);
+        let first = stream.next_record().await.unwrap().unwrap();
+        assert!(matches!(
+            first.get("a"),
+            Some(Value::Number(Number::Int(1)))
+        ));
+        let second = stream.next_record().await.unwrap().unwrap();
+        assert!(matches!(second.get("b"), Some(Value::String(b)) if b == "y"));
+        assert!(stream.next_record().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn stream_records_spanning_lines() {
+        let mut stream = CsvRecordStream::new(&b"a,b\r\n\"two\r\nlines\",x\r\n3,\"open"[..]);
+        let first = stream.next_record().await.unwrap().unwrap();
+        assert!(matches!(first.get("a"), Some(Value::String(a)) if a == "two\r\nlines"));
+        assert!(stream.next_record().await.unwrap().is_err());
+        assert!(stream.next_record().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn stream_records_with_any_line_ending() {
+        for text in [
+            "a,b\r1,2\r3,4\r",
+            "a,b\r\n1,2\r3,4\n5,6",
+            "a,b\n1,2\r\n3,4\r5,6\n",
+        ] {
+            let streamed = stream_like_the_parser(text.as_bytes(), &CsvOptions::new()).await;
+            assert!(!streamed.is_empty());
+        }
+
+        let mut stream = CsvRecordStream::new(&b"a,b\r1,2\r3\r4,5\r"[..]);
+        assert!(stream.next_record().await.unwrap().is_ok());
+        let error = stream.next_record().await.unwrap().unwrap_err();
+        assert!(error.message.starts_with("line 3: "), "{}", error.message);
+        assert!(stream.next_record().await.unwrap().is_ok());
+        assert!(stream.next_record().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn stream_with_options() {
+        let streamed = stream_like_the_parser(b"\xEF\xBB\xBFa,b\n1,2\n", &CsvOptions::new()).await;
+        assert_eq!(streamed[0].header(), &["a", "b"]);
+
+        let options = CsvOptions::new()
+            .dialect(CsvDialect::new().delimiter(";"))
+            .skip_lines(2)
+            .column_type("zip", CsvType::String)
+            .null_values(["NULL"]);
+        let text = b"export\n\"open;quote\nzip;n\n02134;NULL\n1;2\n";
+        let streamed = stream_like_the_parser(text, &options).await;
+        assert_eq!(streamed.len(), 2);
+        assert!(matches!(&streamed[0]["zip"], Value::String(zip) if zip == "02134"));
+        assert_eq!(streamed[0]["n"], Value::Null);
+
+        let options = CsvOptions::new().header(CsvHeader::None);
+        let streamed = stream_like_the_parser(b"1,x\n2,y\n", &options).await;
+        assert_eq!(streamed[1].header(), &["column_1", "column_2"]);
+
+        let options = CsvOptions::new().encoding(CsvEncoding::Latin1);
+        let streamed = stream_like_the_parser(b"name\nCaf\xE9\n", &options).await;
+        assert!(matches!(&streamed[0]["name"], Value::String(name) if name == "Caf\u{e9}"));
+
+        let mut stream = CsvRecordStream::new(&b"\xFF\xFEa\x00\n\x00"[..]);
+        assert!(stream.next_record().await.unwrap().is_err());
+    }
+}
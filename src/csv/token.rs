@@ -1,14 +1,17 @@
-use std::{
-    io::{BufRead, BufReader},
-    iter::Peekable,
-};
-
 use crate::{ParsingError, ParsonResult};
 
+use super::{
+    options::{CsvDialect, CsvEscape, CsvTerminator},
+    words::Words,
+};
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Number {
     Int(i64),
     Float(f64),
+    /// A decimal number as written, without a `+` sign, from a
+    /// [`CsvType::Decimal`](super::CsvType::Decimal) column.
+    Decimal(String),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -23,7 +26,9 @@ impl TryFrom<f64> for Token {
     type Error = ParsingError;
 
     fn try_from(value: f64) -> Result<Self, Self::Error> {
-        let value = if value.fract() == 0.0 {
+        // i64::MAX as f64 is 2^63, one past the largest i64
+        let fits = value >= i64::MIN as f64 && value < i64::MAX as f64;
+        let value = if value.fract() == 0.0 && fits {
             Token::Number(Number::Int(value as i64))
         } else {
             Token::Number(Number::Float(value))
@@ -32,156 +37,405 @@ impl TryFrom<f64> for Token {
     }
 }
 
-pub struct Tokenizer {
-    pub tokens: Vec<Vec<Token>>,
+/// A record split off the start of some text by [`Tokenizer::tokenize_record`].
+#[cfg(any(feature = "async", test))]
+#[derive(Debug, PartialEq)]
+pub(crate) struct RecordText {
+    pub(crate) fields: Vec<Field>,
+    /// The bytes the record takes up, its terminator included.
+    pub(crate) len: usize,
+    /// The line breaks and terminators in those bytes.
+    pub(crate) lines: usize,
+    /// Whether the record ended at a terminator rather than at the end of
+    /// the text, after which it might go on.
+    pub(crate) terminated: bool,
 }
 
 #[derive(Debug)]
-struct Record(Vec<Token>);
-
-impl Record {
-    pub fn parse_number(
-        iterator: &mut Peekable<impl Iterator<Item = char>>,
-    ) -> ParsonResult<Token> {
-        //parse as number
-        //parse until comma
-        let mut number_string = String::new();
-        for digit in iterator.by_ref() {
-            if digit == ',' {
-                break;
+pub struct Tokenizer {
+    pub records: Vec<Vec<Field>>,
+    /// The line each record starts on.
+    pub lines: Vec<usize>,
+    /// The byte offset each record starts at.
+    pub offsets: Vec<usize>,
+}
+
+/// A field as written in the file, before its type is worked out.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Field {
+    pub(crate) value: String,
+    pub(crate) quoted: bool,
+}
+
+impl Field {
+    pub(crate) fn to_token(&self, words: &Words) -> ParsonResult<Token> {
+        if words.is_null(self) {
+            return Ok(Token::Null);
+        }
+        if let Some(boolean) = words.boolean(&self.value) {
+            return Ok(Token::Boolean(boolean));
+        }
+        let value = self.value.clone();
+        if !self.quoted && value.starts_with(|char| matches!(char, '0'..='9' | '-' | '+')) {
+            // zero-padded codes keep their zeros, and integers too long for an
+            // i64 keep their digits rather than being rounded to an f64
+            if has_leading_zero(&value) {
+                return Ok(Token::String(value));
             }
-            number_string.push(digit);
+            if let Ok(int) = value.parse::<i64>() {
+                return Ok(Token::Number(Number::Int(int)));
+            }
+            if is_long_integer(&value) {
+                return Ok(Token::String(value));
+            }
+            return match value.parse::<f64>() {
+                Ok(num) => num.try_into(),
+                // should return a string since could not parse it into Token::Number
+                Err(_) => Ok(Token::String(value)),
+            };
         }
+        Ok(Token::String(value))
+    }
+}
 
-        match number_string.parse::<f64>() {
-            Ok(num) => num.try_into(),
-            Err(_) => {
-                // should return a string since could not parse it into Token::Number
-                Ok(Token::String(number_string))
-            }
+/// Whether `value` is a number written with leading zeros, like `007`.
+pub(crate) fn has_leading_zero(value: &str) -> bool {
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value).as_bytes();
+    digits.len() > 1 && digits[0] == b'0' && digits[1].is_ascii_digit()
+}
+
+/// Whether `value` is an integer, all digits, too long for an i64.
+pub(crate) fn is_long_integer(value: &str) -> bool {
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+    !digits.is_empty()
+        && digits.bytes().all(|byte| byte.is_ascii_digit())
+        && value.parse::<i64>().is_err()
+}
+
+/// How a field ended.
+#[derive(Debug, PartialEq)]
+enum FieldEnd {
+    Delimiter,
+    Terminator,
+    Eof,
+}
+
+/// Splits text into records and fields following a [`CsvDialect`]. Quoted
+/// fields may hold delimiters, quotes and line breaks.
+struct Records<'a> {
+    text: &'a str,
+    dialect: &'a CsvDialect,
+    pos: usize,
+    /// 1-based line of `pos`, for error messages.
+    line: usize,
+    /// Set when the text ends inside a quoted field.
+    incomplete: bool,
+    /// Whether the last record ended at a terminator rather than at the end
+    /// of the text.
+    terminated: bool,
+}
+
+impl<'a> Records<'a> {
+    fn new(text: &'a str, dialect: &'a CsvDialect) -> Self {
+        Self {
+            text,
+            dialect,
+            pos: 0,
+            line: 1,
+            incomplete: false,
+            terminated: false,
         }
     }
 
-    pub fn parse_string(
-        iterator: &mut Peekable<impl Iterator<Item = char>>,
-    ) -> ParsonResult<Token> {
-        let mut string_value = String::new();
-        for inner_char in iterator.by_ref() {
-            if inner_char == ',' {
-                break;
+    fn next_char(&mut self) -> Option<char> {
+        let char = self.text[self.pos..].chars().next()?;
+        self.pos += char.len_utf8();
+        if char == '\n' {
+            self.line += 1;
+        }
+        Some(char)
+    }
+
+    /// The length of the record terminator at the current position.
+    fn terminator_len(&self) -> Option<usize> {
+        let rest = &self.text[self.pos..];
+        match self.dialect.terminator {
+            CsvTerminator::Lines if rest.starts_with("\r\n") => Some(2),
+            CsvTerminator::Lines if rest.starts_with(['\n', '\r']) => Some(1),
+            CsvTerminator::Char(terminator) if rest.starts_with(terminator) => {
+                Some(terminator.len_utf8())
             }
-            string_value.push(inner_char);
+            _ => None,
         }
+    }
 
-        let token = if &string_value == "true" || &string_value == "false" {
-            Token::Boolean(string_value == "true")
-        } else if string_value.is_empty() {
-            Token::Null
-        } else {
-            Token::String(string_value)
-        };
-        Ok(token)
+    fn skip_terminator(&mut self, len: usize) {
+        self.pos += len;
+        self.line += 1;
     }
 
-    pub fn parse_string_with_double_quotes(
-        iterator: &mut Peekable<impl Iterator<Item = char>>,
-    ) -> ParsonResult<Token> {
-        iterator.next();
-        let mut value = String::new();
-        while let Some(inner_char) = iterator.next() {
-            if inner_char == '"' {
-                //peek the next
-                if let Some('"') = iterator.peek() {
-                    iterator.next();
-                } else {
-                    iterator.next();
+    /// Consumes the delimiter or record terminator at the current position.
+    fn field_end(&mut self) -> Option<FieldEnd> {
+        let rest = &self.text[self.pos..];
+        if rest.is_empty() {
+            return Some(FieldEnd::Eof);
+        }
+        if rest.starts_with(self.dialect.delimiter.as_str()) {
+            self.pos += self.dialect.delimiter.len();
+            return Some(FieldEnd::Delimiter);
+        }
+        let terminator = self.terminator_len()?;
+        self.skip_terminator(terminator);
+        Some(FieldEnd::Terminator)
+    }
+
+    /// Skips `count` lines without looking at quotes, for preambles that are
+    /// not csv at all. Returns whether the text held that many.
+    fn skip_lines(&mut self, count: usize) -> bool {
+        for _ in 0..count {
+            loop {
+                if self.pos == self.text.len() {
+                    return false;
+                }
+                if let Some(terminator) = self.terminator_len() {
+                    self.skip_terminator(terminator);
                     break;
                 }
+                self.next_char();
             }
-            value.push(inner_char);
         }
-        let token = if &value == "true" || &value == "false" {
-            Token::Boolean(value == "true")
-        } else {
-            Token::String(value)
-        };
-        Ok(token)
+        true
     }
-}
 
-impl TryFrom<String> for Record {
-    type Error = ParsingError;
+    /// Reads the character after a backslash escape.
+    fn escaped(&mut self) -> Option<char> {
+        self.next_char().map(|char| match char {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            _ => char,
+        })
+    }
+
+    fn read_field(&mut self) -> ParsonResult<(Field, FieldEnd)> {
+        match self.dialect.quote {
+            Some(quote) if self.text[self.pos..].starts_with(quote) => {
+                self.pos += quote.len_utf8();
+                self.read_quoted(quote)
+            }
+            _ => self.read_unquoted(),
+        }
+    }
 
-    fn try_from(value: String) -> Result<Self, Self::Error> {
-        let mut tokens: Vec<Token> = vec![];
-        let mut iterator = value.chars().peekable();
-        while let Some(char) = iterator.peek() {
-            match char {
-                '0'..='9' | '-' | '+' => {
-                    let token = Self::parse_number(&mut iterator)?;
-                    tokens.push(token);
+    fn read_unquoted(&mut self) -> ParsonResult<(Field, FieldEnd)> {
+        let mut value = String::new();
+        loop {
+            if let Some(end) = self.field_end() {
+                return Ok((
+                    Field {
+                        value,
+                        quoted: false,
+                    },
+                    end,
+                ));
+            }
+            match self.next_char() {
+                Some('\\') if self.dialect.escape == CsvEscape::Backslash => {
+                    value.push(self.escaped().unwrap_or('\\'))
                 }
-                '"' => {
-                    let token = Self::parse_string_with_double_quotes(&mut iterator)?;
-                    tokens.push(token);
+                Some(char) => value.push(char),
+                None => unreachable!("field_end handles the end of the text"),
+            }
+        }
+    }
+
+    fn read_quoted(&mut self, quote: char) -> ParsonResult<(Field, FieldEnd)> {
+        let start = self.line;
+        let mut value = String::new();
+        loop {
+            match self.next_char() {
+                None => {
+                    self.incomplete = true;
+                    return Err(ParsingError {
+                        message: format!("line {}: quoted field is never closed", start),
+                    });
+                }
+                Some('\\') if self.dialect.escape == CsvEscape::Backslash => {
+                    if let Some(char) = self.escaped() {
+                        value.push(char);
+                    }
                 }
-                _ => {
-                    let token = Self::parse_string(&mut iterator)?;
-                    tokens.push(token);
+                Some(char) if char == quote => {
+                    let doubled = self.dialect.escape == CsvEscape::Doubled
+                        && self.text[self.pos..].starts_with(quote);
+                    if !doubled {
+                        break;
+                    }
+                    self.pos += quote.len_utf8();
+                    value.push(quote);
                 }
+                Some(char) => value.push(char),
             }
         }
+        match self.field_end() {
+            Some(end) => Ok((
+                Field {
+                    value,
+                    quoted: true,
+                },
+                end,
+            )),
+            None => Err(ParsingError {
+                message: format!(
+                    "line {}: unexpected {:?} after a closing quote",
+                    self.line,
+                    self.text[self.pos..].chars().next().unwrap_or_default()
+                ),
+            }),
+        }
+    }
 
-        Ok(Record(tokens))
+    /// Reads the fields of the next record, `None` at the end of the text.
+    fn next_record(&mut self) -> Option<ParsonResult<Vec<Field>>> {
+        if self.pos == self.text.len() {
+            return None;
+        }
+        let mut fields = vec![];
+        loop {
+            match self.read_field() {
+                Ok((field, end)) => {
+                    fields.push(field);
+                    if end != FieldEnd::Delimiter {
+                        self.terminated = end == FieldEnd::Terminator;
+                        return Some(Ok(fields));
+                    }
+                }
+                Err(error) => return Some(Err(error)),
+            }
+        }
     }
 }
 
 impl Tokenizer {
-    pub fn new(reader: BufReader<&[u8]>) -> ParsonResult<Self> {
-        Ok(Self {
-            tokens: Self::parse_tokens(reader)?,
-        })
+    /// Splits `text` into records, after skipping its first `skip_lines` lines.
+    pub fn new(text: &str, dialect: &CsvDialect, skip_lines: usize) -> ParsonResult<Self> {
+        Self::parse_records(text, dialect, skip_lines)
     }
 
-    fn parse_tokens(reader: BufReader<&[u8]>) -> ParsonResult<Vec<Vec<Token>>> {
+    fn parse_records(text: &str, dialect: &CsvDialect, skip_lines: usize) -> ParsonResult<Self> {
         let mut value = vec![];
+        let mut lines = vec![];
+        let mut offsets = vec![];
 
-        let mut line_length = 0;
+        let mut line_length = None;
 
-        for (idx, line) in reader.lines().enumerate() {
-            let line = line.map_err(|_| ParsingError {
-                message: "failed to tokenize buf".to_string(),
-            })?;
-            let line: Record = line.try_into()?;
-            if idx == 0 {
-                line_length = line.0.len()
-            }
+        let mut records = Records::new(text, dialect);
+        records.skip_lines(skip_lines);
+        loop {
+            let (line, offset) = (records.line, records.pos);
+            let Some(fields) = records.next_record() else {
+                break;
+            };
+            let record = Self::check_length(fields?, line, line_length)?;
+            line_length.get_or_insert(record.len());
 
-            if idx != 0 && line.0.len() != line_length {
-                return Err(ParsingError {
-                    message: format!("lines do not have the same number of columns {line_length}"),
-                });
-            }
+            value.push(record);
+            lines.push(line);
+            offsets.push(offset);
+        }
+
+        Ok(Self {
+            records: value,
+            lines,
+            offsets,
+        })
+    }
+
+    #[cfg(test)]
+    fn parse_tokens(text: &str, dialect: &CsvDialect) -> ParsonResult<Vec<Vec<Token>>> {
+        Self::parse_records(text, dialect, 0)?
+            .records
+            .iter()
+            .map(|record| {
+                record
+                    .iter()
+                    .map(|field| field.to_token(&Words::default()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Skips the first `count` lines of `text` as [`Tokenizer::new`] does.
+    /// Returns the bytes and lines skipped, or `None` when `text` ends before
+    /// the last of those lines does.
+    #[cfg(feature = "async")]
+    pub(crate) fn skip_lines(
+        text: &str,
+        dialect: &CsvDialect,
+        count: usize,
+    ) -> Option<(usize, usize)> {
+        let mut records = Records::new(text, dialect);
+        records
+            .skip_lines(count)
+            .then_some((records.pos, records.line - 1))
+    }
 
-            value.push(line.0);
+    /// Splits the record at the start of `text`, or returns `None` when
+    /// `text` ends inside a quoted field and more input is needed.
+    #[cfg(any(feature = "async", test))]
+    pub(crate) fn tokenize_record(
+        text: &str,
+        dialect: &CsvDialect,
+    ) -> ParsonResult<Option<RecordText>> {
+        let mut records = Records::new(text, dialect);
+        match records.next_record() {
+            None => Ok(Some(RecordText {
+                fields: vec![],
+                len: 0,
+                lines: 0,
+                terminated: false,
+            })),
+            Some(Err(_)) if records.incomplete => Ok(None),
+            Some(fields) => Ok(Some(RecordText {
+                fields: fields?,
+                len: records.pos,
+                lines: records.line - 1,
+                terminated: records.terminated,
+            })),
         }
+    }
 
-        Ok(value)
+    pub(crate) fn check_length(
+        fields: Vec<Field>,
+        line: usize,
+        line_length: Option<usize>,
+    ) -> ParsonResult<Vec<Field>> {
+        match line_length {
+            Some(line_length) if fields.len() != line_length => Err(ParsingError {
+                message: format!(
+                    "line {line}: lines do not have the same number of columns {line_length}"
+                ),
+            }),
+            _ => Ok(fields),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::io::BufReader;
-
-    use crate::csv::token::{Number, Token};
+    use crate::csv::{
+        options::{CsvDialect, CsvEscape, CsvTerminator},
+        token::{Number, Token},
+        words::Words,
+    };
 
     use super::Tokenizer;
 
     #[test]
     fn fail_tokenizing_uneven_number_of_columns() {
         let csv_string = "test1,test2,num1,num2,additional,\nval1,val2,2,3.4";
-        let tokens = Tokenizer::parse_tokens(BufReader::new(csv_string.as_bytes()));
+        let tokens = Tokenizer::parse_tokens(csv_string, &CsvDialect::default());
         assert!(tokens.is_err());
         assert!(tokens
             .unwrap_err()
@@ -192,7 +446,7 @@ mod tests {
     #[test]
     fn tokenize_escaped_string() {
         let csv_string = "test1,test2,num1,num2\n\"val1\",val2,2,3.4";
-        let tokens = Tokenizer::parse_tokens(BufReader::new(csv_string.as_bytes()));
+        let tokens = Tokenizer::parse_tokens(csv_string, &CsvDialect::default());
         assert!(tokens.is_ok());
         let tokens = tokens.unwrap();
         assert_eq!(tokens.len(), 2);
@@ -205,7 +459,7 @@ mod tests {
         assert_eq!(token_value, "val1".to_string());
 
         let csv_string = "test1,test2,num1,num2\n\"va\"\"l1\",val2,2,3.4";
-        let tokens = Tokenizer::parse_tokens(BufReader::new(csv_string.as_bytes()));
+        let tokens = Tokenizer::parse_tokens(csv_string, &CsvDialect::default());
         assert!(tokens.is_ok());
         let tokens = tokens.unwrap();
         assert_eq!(tokens.len(), 2);
@@ -221,14 +475,14 @@ mod tests {
     #[test]
     fn tokenize_bool() {
         let csv_string = "test1,test2,num1,num2,condition\n\"val1\",val2,2,3.4,true";
-        let tokens = Tokenizer::parse_tokens(BufReader::new(csv_string.as_bytes()));
+        let tokens = Tokenizer::parse_tokens(csv_string, &CsvDialect::default());
         assert!(tokens.clone().is_ok_and(|tok| tok.len() == 2));
         let tokens = tokens.unwrap();
         let token = tokens[1][4].clone();
         assert!(matches!(token, Token::Boolean(true)));
 
         let csv_string = "test1,test2,num1,num2,condition\n\"val1\",val2,2,3.4,\"true\"";
-        let tokens = Tokenizer::parse_tokens(BufReader::new(csv_string.as_bytes()));
+        let tokens = Tokenizer::parse_tokens(csv_string, &CsvDialect::default());
         assert!(tokens.clone().is_ok_and(|tok| tok.len() == 2));
         let tokens = tokens.unwrap();
         let token = tokens[1][4].clone();
@@ -238,7 +492,7 @@ mod tests {
     #[test]
     fn tokenize_correctly() {
         let csv_string = "test1,test2,num1,num2\nval1,val2,2,3.4";
-        let tokens = Tokenizer::parse_tokens(BufReader::new(csv_string.as_bytes()));
+        let tokens = Tokenizer::parse_tokens(csv_string, &CsvDialect::default());
         assert!(tokens.is_ok());
         let tokens = tokens.unwrap();
 
@@ -265,20 +519,224 @@ mod tests {
     #[test]
     fn tokenize_empty_values() {
         let csv_string = ",,,\n,,,";
-        let tokens = Tokenizer::parse_tokens(BufReader::new(csv_string.as_bytes()));
+        let tokens = Tokenizer::parse_tokens(csv_string, &CsvDialect::default());
         assert!(tokens.is_ok());
         let tokens = tokens.unwrap();
         assert_eq!(tokens.len(), 2);
         let first_line = tokens[0].clone();
-        assert_eq!(first_line, vec![Token::Null, Token::Null, Token::Null]);
+        assert_eq!(first_line, vec![Token::Null; 4]);
         let second_line = tokens[1].clone();
-        assert_eq!(second_line, vec![Token::Null, Token::Null, Token::Null]);
+        assert_eq!(second_line, vec![Token::Null; 4]);
     }
 
     #[test]
     fn tokenize_string_that_start_with_number() {
         let csv_string = "test,test1,test2,test3\n1test,1test1,1test2,1test3";
-        let tokens = Tokenizer::parse_tokens(BufReader::new(csv_string.as_bytes()));
+        let tokens = Tokenizer::parse_tokens(csv_string, &CsvDialect::default());
         assert!(tokens.is_ok());
     }
+
+    #[test]
+    fn tokenize_with_dialects() {
+        let semicolon = CsvDialect::new().delimiter(";");
+        let tokens = Tokenizer::parse_tokens("a;b\n\"x;y\";1,5", &semicolon).unwrap();
+        assert_eq!(
+            tokens[1],
+            vec![
+                Token::String("x;y".to_string()),
+                Token::String("1,5".to_string())
+            ]
+        );
+
+        let multi_byte = CsvDialect::new().delimiter("¦¦");
+        let tokens = Tokenizer::parse_tokens("a¦¦b¦c¦¦2", &multi_byte).unwrap();
+        assert_eq!(
+            tokens[0],
+            vec![
+                Token::String("a".to_string()),
+                Token::String("b¦c".to_string()),
+                Token::Number(Number::Int(2)),
+            ]
+        );
+
+        let single_quote = CsvDialect::new().quote(Some('\''));
+        let tokens = Tokenizer::parse_tokens("'it''s, ok',\"b\"", &single_quote).unwrap();
+        assert_eq!(
+            tokens[0],
+            vec![
+                Token::String("it's, ok".to_string()),
+                Token::String("\"b\"".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_backslash_escapes() {
+        let tokens = Tokenizer::parse_tokens(
+            "name\tnote\r\n\"a\"\tone\\ttwo\\\\three\r\n",
+            &CsvDialect::tsv(),
+        )
+        .unwrap();
+        assert_eq!(
+            tokens[1],
+            vec![
+                Token::String("\"a\"".to_string()),
+                Token::String("one\ttwo\\three".to_string()),
+            ]
+        );
+
+        let tokens =
+            Tokenizer::parse_tokens("\"say \\\"hi\\\"\",a\\,b\n", &CsvDialect::unix()).unwrap();
+        assert_eq!(
+            tokens[0],
+            vec![
+                Token::String("say \"hi\"".to_string()),
+                Token::String("a,b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_records_at_the_terminator() {
+        let dialect = CsvDialect::new()
+            .escape(CsvEscape::Doubled)
+            .terminator(CsvTerminator::Char('|'));
+        let tokens = Tokenizer::parse_tokens("a,b|1,2|3,4|", &dialect).unwrap();
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(
+            tokens[2],
+            vec![Token::Number(Number::Int(3)), Token::Number(Number::Int(4))]
+        );
+    }
+
+    #[test]
+    fn tokenize_quoted_line_breaks_and_mixed_endings() {
+        let csv_string = "a,b\r\n\"one\ntwo\",\"x\r\ny\"\n1,2\r3,4\r\n";
+        let tokens = Tokenizer::parse_tokens(csv_string, &CsvDialect::default()).unwrap();
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(
+            tokens[1],
+            vec![
+                Token::String("one\ntwo".to_string()),
+                Token::String("x\r\ny".to_string()),
+            ]
+        );
+        assert_eq!(
+            tokens[3],
+            vec![Token::Number(Number::Int(3)), Token::Number(Number::Int(4))]
+        );
+    }
+
+    #[test]
+    fn keep_trailing_empty_fields() {
+        let tokens = Tokenizer::parse_tokens("a,b,\n1,,\"\"\n", &CsvDialect::default()).unwrap();
+        assert_eq!(
+            tokens[0],
+            vec![
+                Token::String("a".to_string()),
+                Token::String("b".to_string()),
+                Token::Null,
+            ]
+        );
+        assert_eq!(
+            tokens[1],
+            vec![
+                Token::Number(Number::Int(1)),
+                Token::Null,
+                Token::String("".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn report_malformed_quotes_with_their_line() {
+        let error = Tokenizer::parse_tokens("a,b\n\"x\"y,1", &CsvDialect::default()).unwrap_err();
+        assert_eq!(
+            error.message,
+            "line 2: unexpected 'y' after a closing quote"
+        );
+
+        let error =
+            Tokenizer::parse_tokens("a,b\n1,\"open\n2,3\n", &CsvDialect::default()).unwrap_err();
+        assert_eq!(error.message, "line 2: quoted field is never closed");
+
+        let error =
+            Tokenizer::parse_tokens("a,b\n\"1\n\",2\n3\n", &CsvDialect::default()).unwrap_err();
+        assert_eq!(
+            error.message,
+            "line 4: lines do not have the same number of columns 2"
+        );
+    }
+
+    #[test]
+    fn tokenize_one_record_at_a_time() {
+        let dialect = CsvDialect::default();
+        let record = Tokenizer::tokenize_record("1,\"a\nb\"\n2,c\n", &dialect)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            record
+                .fields
+                .iter()
+                .map(|field| field.to_token(&Words::default()).unwrap())
+                .collect::<Vec<_>>(),
+            vec![
+                Token::Number(Number::Int(1)),
+                Token::String("a\nb".to_string())
+            ]
+        );
+        assert_eq!((record.len, record.lines, record.terminated), (8, 2, true));
+        assert_eq!(
+            Tokenizer::tokenize_record("1,\"a\n", &dialect).unwrap(),
+            None
+        );
+        let record = Tokenizer::tokenize_record("3,4\r5,6", &dialect)
+            .unwrap()
+            .unwrap();
+        assert_eq!((record.len, record.terminated), (4, true));
+        let record = Tokenizer::tokenize_record("5,6", &dialect)
+            .unwrap()
+            .unwrap();
+        assert_eq!((record.len, record.terminated), (3, false));
+    }
+
+    #[test]
+    fn skip_preamble_lines() {
+        let text = "Report \"Q1\r\ngenerated today\na,b\n1,2\n";
+        let Tokenizer {
+            records,
+            lines,
+            offsets,
+        } = Tokenizer::new(text, &CsvDialect::default(), 2).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0][0].value, "a");
+        assert_eq!(lines, vec![3, 4]);
+        assert_eq!(offsets, vec![28, 32]);
+
+        let error = Tokenizer::parse_records("x\na,b\n1\n", &CsvDialect::default(), 1).unwrap_err();
+        assert!(error.message.starts_with("line 3: "));
+    }
+
+    #[test]
+    fn keep_long_integers_and_leading_zeros() {
+        let tokens = Tokenizer::parse_tokens(
+            "3588358312619246,007,-01.5,0,0.5,-0,12345678901234567890,-99999999999999999999,1e30",
+            &CsvDialect::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            tokens[0],
+            vec![
+                Token::Number(Number::Int(3588358312619246)),
+                Token::String("007".to_string()),
+                Token::String("-01.5".to_string()),
+                Token::Number(Number::Int(0)),
+                Token::Number(Number::Float(0.5)),
+                Token::Number(Number::Int(0)),
+                Token::String("12345678901234567890".to_string()),
+                Token::String("-99999999999999999999".to_string()),
+                Token::Number(Number::Float(1e30)),
+            ]
+        );
+    }
 }
@@ -1,13 +1,21 @@
-use crate::ParsingError;
+use std::time::SystemTime;
+
+use crate::{Date, DateTime, JsonValue, ParsingError, Time};
 
 use super::token::{Number, Token};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     String(String),
     Number(Number),
     Null,
     Boolean(bool),
+    /// Only produced for [`CsvType::Date`](super::CsvType::Date) columns.
+    Date(Date),
+    /// Only produced for [`CsvType::Time`](super::CsvType::Time) columns.
+    Time(Time),
+    /// Only produced for [`CsvType::DateTime`](super::CsvType::DateTime) columns.
+    DateTime(DateTime),
 }
 
 impl From<Token> for Value {
@@ -31,6 +39,24 @@ impl From<&Token> for Value {
     }
 }
 
+impl From<Value> for JsonValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::String(string) => JsonValue::String(string),
+            Value::Number(Number::Int(number)) => JsonValue::from(number),
+            Value::Number(Number::Float(number)) => JsonValue::from(number),
+            Value::Number(Number::Decimal(number)) => {
+                JsonValue::from(number.parse::<f64>().unwrap_or_default())
+            }
+            Value::Null => JsonValue::Null,
+            Value::Boolean(boolean) => JsonValue::Boolean(boolean),
+            Value::Date(date) => JsonValue::String(date.to_string()),
+            Value::Time(time) => JsonValue::String(time.to_string()),
+            Value::DateTime(date_time) => JsonValue::String(date_time.to_string()),
+        }
+    }
+}
+
 impl TryFrom<Value> for String {
     type Error = ParsingError;
 
@@ -63,6 +89,11 @@ impl TryFrom<Value> for f64 {
     fn try_from(value: Value) -> Result<Self, Self::Error> {
         match value {
             Value::Number(Number::Float(number)) => Ok(number),
+            Value::Number(Number::Decimal(ref number)) => {
+                number.parse().map_err(|_| ParsingError {
+                    message: format!("value {:?} is not a float", value),
+                })
+            }
             _ => Err(ParsingError {
                 message: format!("value {:?} is not a float", value),
             }),
@@ -82,3 +113,18 @@ impl TryFrom<Value> for bool {
         }
     }
 }
+
+impl TryFrom<Value> for SystemTime {
+    type Error = ParsingError;
+
+    /// Takes dates as midnight UTC and date times without an offset as UTC.
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Date(date) => Ok(date.to_system_time()),
+            Value::DateTime(date_time) => Ok(date_time.to_system_time()),
+            _ => Err(ParsingError {
+                message: format!("value {:?} is not a date", value),
+            }),
+        }
+    }
+}
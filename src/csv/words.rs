@@ -0,0 +1,112 @@
+use super::{options::BoolWords, token::Field, CsvOptions};
+
+/// The words that stand for null, true and false in a column.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Words {
+    nulls: Vec<String>,
+    trues: Vec<String>,
+    falses: Vec<String>,
+    quoted_empty_is_null: bool,
+}
+
+impl Default for Words {
+    fn default() -> Self {
+        Self {
+            nulls: vec![],
+            trues: vec!["true".to_string()],
+            falses: vec!["false".to_string()],
+            quoted_empty_is_null: false,
+        }
+    }
+}
+
+impl Words {
+    /// The words of every column without settings of its own.
+    pub(crate) fn new(options: &CsvOptions) -> Self {
+        let defaults = Self::default();
+        let (trues, falses) = options
+            .bool_values
+            .clone()
+            .unwrap_or((defaults.trues, defaults.falses));
+        Self {
+            nulls: options.null_values.clone(),
+            trues,
+            falses,
+            quoted_empty_is_null: options.quoted_empty_is_null,
+        }
+    }
+
+    /// These words, with the null and boolean words of a column replaced.
+    pub(crate) fn with(&self, nulls: Option<Vec<String>>, bools: Option<BoolWords>) -> Self {
+        let mut words = self.clone();
+        if let Some(nulls) = nulls {
+            words.nulls = nulls;
+        }
+        if let Some((trues, falses)) = bools {
+            words.trues = trues;
+            words.falses = falses;
+        }
+        words
+    }
+
+    /// Whether `field` is null: an empty field, unless it is quoted and quoted
+    /// empty strings are kept, or one of the null words.
+    pub(crate) fn is_null(&self, field: &Field) -> bool {
+        match field.value.is_empty() {
+            true => !field.quoted || self.quoted_empty_is_null,
+            false => self.nulls.contains(&field.value),
+        }
+    }
+
+    pub(crate) fn boolean(&self, text: &str) -> Option<bool> {
+        if self.trues.iter().any(|word| word == text) {
+            Some(true)
+        } else if self.falses.iter().any(|word| word == text) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::csv::{token::Field, CsvOptions};
+
+    use super::Words;
+
+    fn field(value: &str, quoted: bool) -> Field {
+        Field {
+            value: value.to_string(),
+            quoted,
+        }
+    }
+
+    #[test]
+    fn match_null_and_boolean_words() {
+        let words = Words::default();
+        assert!(words.is_null(&field("", false)));
+        assert!(!words.is_null(&field("", true)));
+        assert!(!words.is_null(&field("NULL", false)));
+        assert_eq!(words.boolean("true"), Some(true));
+        assert_eq!(words.boolean("TRUE"), None);
+
+        let options = CsvOptions::new()
+            .null_values(["NULL", "\\N"])
+            .bool_values(["Y", "1"], ["N", "0"])
+            .quoted_empty_is_null(true);
+        let words = Words::new(&options);
+        assert!(words.is_null(&field("", true)));
+        assert!(words.is_null(&field("\\N", false)));
+        assert!(words.is_null(&field("NULL", true)));
+        assert!(!words.is_null(&field("null", false)));
+        assert_eq!(words.boolean("1"), Some(true));
+        assert_eq!(words.boolean("N"), Some(false));
+        assert_eq!(words.boolean("true"), None);
+
+        let column = words.with(Some(vec!["-".to_string()]), None);
+        assert!(column.is_null(&field("-", false)));
+        assert!(!column.is_null(&field("NULL", false)));
+        assert_eq!(column.boolean("Y"), Some(true));
+    }
+}
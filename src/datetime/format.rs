@@ -0,0 +1,375 @@
+use std::fmt::Display;
+
+use crate::{ParsingError, ParsonResult};
+
+use super::{Date, DateTime, Temporal, Time};
+
+/// A pattern dates and times are read with, in the style of `strftime`:
+///
+/// - `%Y` a four digit year, `%m` a month and `%d` a day of one or two digits
+/// - `%H` an hour of one or two digits, `%I` the same on a 12-hour clock
+///   with `%p` for `AM` or `PM`, `%M` minutes and `%S` seconds of two digits
+/// - `%f` optional fractional seconds, like `.250`
+/// - `%z` an offset from UTC, `Z`, `+02:00` or `+0200`
+/// - `%%` a percent sign; any other character stands for itself, and `T`
+///   also matches a lowercase `t`
+///
+/// A format with only date fields reads [`Temporal::Date`], one with only
+/// time fields [`Temporal::Time`], and one with both [`Temporal::DateTime`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateFormat {
+    pattern: String,
+    items: Vec<Item>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Item {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Hour12,
+    Minute,
+    Second,
+    Fraction,
+    Meridiem,
+    Offset,
+    Literal(char),
+}
+
+/// What a format reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Date,
+    Time,
+    DateTime,
+}
+
+impl DateFormat {
+    pub fn new(pattern: &str) -> ParsonResult<Self> {
+        let mut items = vec![];
+        let mut chars = pattern.chars();
+        while let Some(char) = chars.next() {
+            if char != '%' {
+                items.push(Item::Literal(char));
+                continue;
+            }
+            let item = match chars.next() {
+                Some('Y') => Item::Year,
+                Some('m') => Item::Month,
+                Some('d') => Item::Day,
+                Some('H') => Item::Hour,
+                Some('I') => Item::Hour12,
+                Some('M') => Item::Minute,
+                Some('S') => Item::Second,
+                Some('f') => Item::Fraction,
+                Some('p') => Item::Meridiem,
+                Some('z') => Item::Offset,
+                Some('%') => Item::Literal('%'),
+                other => {
+                    let field = other.map(|char| format!("%{}", char));
+                    return Err(ParsingError {
+                        message: format!(
+                            "invalid date format {:?}: unknown field {:?}",
+                            pattern,
+                            field.unwrap_or_else(|| "%".to_string())
+                        ),
+                    });
+                }
+            };
+            items.push(item);
+        }
+        let format = Self {
+            pattern: pattern.to_string(),
+            items,
+        };
+        let has = |item| format.items.contains(&item);
+        let missing = if has(Item::Year) || has(Item::Month) || has(Item::Day) {
+            !(has(Item::Year) && has(Item::Month) && has(Item::Day))
+        } else {
+            !(has(Item::Hour) || has(Item::Hour12))
+        };
+        if missing || has(Item::Hour12) != has(Item::Meridiem) {
+            return Err(ParsingError {
+                message: format!(
+                    "invalid date format {:?}: needs a year, month and day, an hour, or both",
+                    pattern
+                ),
+            });
+        }
+        Ok(format)
+    }
+
+    /// `YYYY-MM-DD`, as in `2021-05-22`.
+    pub fn iso_date() -> Self {
+        Self::preset("%Y-%m-%d")
+    }
+
+    /// An ISO 8601 date and time without an offset, as in `2021-05-22T20:05:00`.
+    pub fn iso_date_time() -> Self {
+        Self::preset("%Y-%m-%dT%H:%M:%S%f")
+    }
+
+    /// An RFC 3339 timestamp, as in `2021-05-22T20:05:00.5+02:00`.
+    pub fn rfc3339() -> Self {
+        Self::preset("%Y-%m-%dT%H:%M:%S%f%z")
+    }
+
+    /// `M/D/YYYY`, as in `5/22/2021`.
+    pub fn us_date() -> Self {
+        Self::preset("%m/%d/%Y")
+    }
+
+    /// `D.M.YYYY`, as in `22.5.2021`.
+    pub fn european_date() -> Self {
+        Self::preset("%d.%m.%Y")
+    }
+
+    /// `HH:MM:SS`, as in `20:05:00`.
+    pub fn iso_time() -> Self {
+        Self::preset("%H:%M:%S%f")
+    }
+
+    /// A 12-hour clock time, as in `8:05 PM`.
+    pub fn time_12h() -> Self {
+        Self::preset("%I:%M %p")
+    }
+
+    /// The formats tried when none are configured: every preset, with
+    /// `YYYY-MM-DD HH:MM:SS` as well.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::iso_date(),
+            Self::rfc3339(),
+            Self::iso_date_time(),
+            Self::preset("%Y-%m-%d %H:%M:%S%f"),
+            Self::us_date(),
+            Self::european_date(),
+            Self::iso_time(),
+            Self::preset("%H:%M"),
+            Self::time_12h(),
+        ]
+    }
+
+    fn preset(pattern: &str) -> Self {
+        Self::new(pattern).expect("presets are valid patterns")
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub(crate) fn kind(&self) -> Kind {
+        let date = self.items.contains(&Item::Year);
+        let time = self.items.contains(&Item::Hour) || self.items.contains(&Item::Hour12);
+        match (date, time) {
+            (true, true) => Kind::DateTime,
+            (true, false) => Kind::Date,
+            _ => Kind::Time,
+        }
+    }
+
+    /// Reads the whole of `text`, `None` if it does not follow the format or
+    /// is not a real date or time.
+    pub fn parse(&self, text: &str) -> Option<Temporal> {
+        let mut rest = text;
+        let (mut year, mut month, mut day) = (0, 0, 0);
+        let (mut hour, mut minute, mut second, mut nanosecond) = (0, 0, 0, 0);
+        let mut pm = None;
+        let mut offset = None;
+        for item in &self.items {
+            match item {
+                Item::Year => year = number(&mut rest, 4, 4)? as i32,
+                Item::Month => month = number(&mut rest, 1, 2)? as u8,
+                Item::Day => day = number(&mut rest, 1, 2)? as u8,
+                Item::Hour => hour = number(&mut rest, 1, 2)? as u8,
+                Item::Hour12 => {
+                    hour = number(&mut rest, 1, 2)? as u8;
+                    if !(1..=12).contains(&hour) {
+                        return None;
+                    }
+                }
+                Item::Minute => minute = number(&mut rest, 2, 2)? as u8,
+                Item::Second => second = number(&mut rest, 2, 2)? as u8,
+                Item::Fraction => nanosecond = fraction(&mut rest)?,
+                Item::Meridiem => {
+                    let meridiem = rest.get(..2)?;
+                    pm = Some(match meridiem.to_ascii_uppercase().as_str() {
+                        "AM" => false,
+                        "PM" => true,
+                        _ => return None,
+                    });
+                    rest = &rest[2..];
+                }
+                Item::Offset => offset = Some(utc_offset(&mut rest)?),
+                Item::Literal(expected) => {
+                    let char = rest.chars().next()?;
+                    let matches = char == *expected || *expected == 'T' && char == 't';
+                    if !matches {
+                        return None;
+                    }
+                    rest = &rest[char.len_utf8()..];
+                }
+            }
+        }
+        if !rest.is_empty() {
+            return None;
+        }
+        if let Some(pm) = pm {
+            hour = hour % 12 + if pm { 12 } else { 0 };
+        }
+        let date = || Date::new(year, month, day);
+        let time = || Time::new(hour, minute, second, nanosecond);
+        let value = match self.kind() {
+            Kind::Date => Temporal::Date(date()?),
+            Kind::Time => Temporal::Time(time()?),
+            Kind::DateTime => Temporal::DateTime(DateTime::new(date()?, time()?, offset)?),
+        };
+        Some(value)
+    }
+}
+
+impl Display for DateFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.pattern)
+    }
+}
+
+/// Takes `min` to `max` ASCII digits off the front of `rest`.
+fn number(rest: &mut &str, min: usize, max: usize) -> Option<u32> {
+    let len = rest
+        .bytes()
+        .take(max)
+        .take_while(|byte| byte.is_ascii_digit())
+        .count();
+    if len < min {
+        return None;
+    }
+    let value = rest[..len].parse().ok()?;
+    *rest = &rest[len..];
+    Some(value)
+}
+
+/// Takes `.` or `,` and up to nine digits off the front of `rest`, if they
+/// are there, as nanoseconds.
+fn fraction(rest: &mut &str) -> Option<u32> {
+    let Some(digits) = rest.strip_prefix(['.', ',']) else {
+        return Some(0);
+    };
+    let len = digits
+        .bytes()
+        .take_while(|byte| byte.is_ascii_digit())
+        .count();
+    if len == 0 || len > 9 {
+        return None;
+    }
+    let value = digits[..len].parse::<u32>().ok()? * 10u32.pow(9 - len as u32);
+    *rest = &digits[len..];
+    Some(value)
+}
+
+/// Takes `Z`, `+HH:MM` or `+HHMM` off the front of `rest`, in minutes.
+fn utc_offset(rest: &mut &str) -> Option<i16> {
+    if let Some(after) = rest.strip_prefix(['Z', 'z']) {
+        *rest = after;
+        return Some(0);
+    }
+    let sign = match rest.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    *rest = &rest[1..];
+    let hours = number(rest, 2, 2)?;
+    if let Some(after) = rest.strip_prefix(':') {
+        *rest = after;
+    }
+    let minutes = number(rest, 2, 2)?;
+    if minutes >= 60 {
+        return None;
+    }
+    Some(sign * (hours * 60 + minutes) as i16)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datetime::{Date, DateTime, Temporal, Time};
+
+    use super::DateFormat;
+
+    fn date(year: i32, month: u8, day: u8) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn parse_with_presets() {
+        assert_eq!(
+            DateFormat::us_date().parse("5/22/2021"),
+            Some(Temporal::Date(date(2021, 5, 22)))
+        );
+        assert_eq!(
+            DateFormat::european_date().parse("22.05.2021"),
+            Some(Temporal::Date(date(2021, 5, 22)))
+        );
+        assert_eq!(DateFormat::us_date().parse("22/5/2021"), None);
+        assert_eq!(
+            DateFormat::time_12h().parse("8:05 PM"),
+            Some(Temporal::Time(Time::new(20, 5, 0, 0).unwrap()))
+        );
+        assert_eq!(
+            DateFormat::time_12h().parse("12:30 am"),
+            Some(Temporal::Time(Time::new(0, 30, 0, 0).unwrap()))
+        );
+
+        let time = Time::new(20, 5, 0, 250_000_000).unwrap();
+        assert_eq!(
+            DateFormat::rfc3339().parse("2021-05-22t20:05:00.25-0530"),
+            Some(Temporal::DateTime(
+                DateTime::new(date(2021, 5, 22), time, Some(-330)).unwrap()
+            ))
+        );
+        assert_eq!(
+            DateFormat::rfc3339()
+                .parse("2021-05-22T20:05:00Z")
+                .unwrap()
+                .to_string(),
+            "2021-05-22T20:05:00Z"
+        );
+        assert_eq!(DateFormat::rfc3339().parse("2021-05-22T20:05:00"), None);
+        assert_eq!(
+            DateFormat::iso_date_time().parse("2021-05-22T20:05:00"),
+            Some(Temporal::DateTime(
+                DateTime::new(date(2021, 5, 22), Time::new(20, 5, 0, 0).unwrap(), None).unwrap()
+            ))
+        );
+        assert_eq!(DateFormat::iso_date().parse("2021-02-30"), None);
+        assert_eq!(DateFormat::iso_date().parse("2021-05-22 "), None);
+    }
+
+    #[test]
+    fn parse_custom_patterns() {
+        let format = DateFormat::new("%d %% %Y/%m").unwrap();
+        assert_eq!(
+            format.parse("1 % 2024/02"),
+            Some(Temporal::Date(date(2024, 2, 1)))
+        );
+        assert_eq!(format.to_string(), "%d %% %Y/%m");
+        for (pattern, message) in [
+            (
+                "%Y-%q",
+                "invalid date format \"%Y-%q\": unknown field \"%q\"",
+            ),
+            ("%Y%", "invalid date format \"%Y%\": unknown field \"%\""),
+            (
+                "%Y-%m",
+                "invalid date format \"%Y-%m\": needs a year, month and day, an hour, or both",
+            ),
+            (
+                "%I:%M",
+                "invalid date format \"%I:%M\": needs a year, month and day, an hour, or both",
+            ),
+        ] {
+            assert_eq!(DateFormat::new(pattern).unwrap_err().message, message);
+        }
+    }
+}
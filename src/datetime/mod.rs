@@ -0,0 +1,356 @@
+mod format;
+
+use std::{
+    fmt::Display,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+pub use format::DateFormat;
+pub(crate) use format::Kind;
+
+/// A calendar date, without a time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    /// Returns `None` for dates that don't exist, like February 30th.
+    pub fn new(year: i32, month: u8, day: u8) -> Option<Self> {
+        let valid = (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month);
+        valid.then_some(Self { year, month, day })
+    }
+
+    /// Parses an ISO 8601 calendar date, `YYYY-MM-DD`.
+    pub fn parse_iso(text: &str) -> Option<Self> {
+        let bytes = text.as_bytes();
+        if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+            return None;
+        }
+        Self::new(
+            digits(&text[0..4])? as i32,
+            digits(&text[5..7])? as u8,
+            digits(&text[8..10])? as u8,
+        )
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Midnight UTC at the start of the date.
+    pub fn to_system_time(&self) -> SystemTime {
+        system_time(self.days_since_epoch() * 86_400, 0)
+    }
+
+    /// Days from 1970-01-01, negative before it.
+    fn days_since_epoch(&self) -> i64 {
+        // Howard Hinnant's days_from_civil, with March as the first month of the year
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = i64::from(self.month);
+        let day_of_year =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+}
+
+impl Display for Date {
+    /// Writes the date as `YYYY-MM-DD`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// A time of day, without a time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    hour: u8,
+    minute: u8,
+    second: u8,
+    nanosecond: u32,
+}
+
+impl Time {
+    /// Returns `None` out of `00:00:00` to `23:59:59.999999999`.
+    pub fn new(hour: u8, minute: u8, second: u8, nanosecond: u32) -> Option<Self> {
+        let valid = hour < 24 && minute < 60 && second < 60 && nanosecond < 1_000_000_000;
+        valid.then_some(Self {
+            hour,
+            minute,
+            second,
+            nanosecond,
+        })
+    }
+
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    pub fn nanosecond(&self) -> u32 {
+        self.nanosecond
+    }
+
+    fn seconds(&self) -> i64 {
+        i64::from(self.hour) * 3600 + i64::from(self.minute) * 60 + i64::from(self.second)
+    }
+}
+
+impl Display for Time {
+    /// Writes the time as `HH:MM:SS`, with as many fraction digits as needed.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)?;
+        if self.nanosecond > 0 {
+            let fraction = format!("{:09}", self.nanosecond);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        Ok(())
+    }
+}
+
+/// A date and a time of day, with the offset from UTC when it is known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DateTime {
+    date: Date,
+    time: Time,
+    offset_minutes: Option<i16>,
+}
+
+impl DateTime {
+    /// `offset_minutes` is east of UTC, e.g. `-300` for `-05:00`. Returns
+    /// `None` for offsets of a day or more.
+    pub fn new(date: Date, time: Time, offset_minutes: Option<i16>) -> Option<Self> {
+        let valid = offset_minutes.map_or(true, |offset| offset.abs() < 24 * 60);
+        valid.then_some(Self {
+            date,
+            time,
+            offset_minutes,
+        })
+    }
+
+    pub fn date(&self) -> Date {
+        self.date
+    }
+
+    pub fn time(&self) -> Time {
+        self.time
+    }
+
+    pub fn offset_minutes(&self) -> Option<i16> {
+        self.offset_minutes
+    }
+
+    /// The instant this is, taking a date time without an offset as UTC.
+    pub fn to_system_time(&self) -> SystemTime {
+        let offset = i64::from(self.offset_minutes.unwrap_or(0)) * 60;
+        let seconds = self.date.days_since_epoch() * 86_400 + self.time.seconds() - offset;
+        system_time(seconds, self.time.nanosecond)
+    }
+}
+
+impl Display for DateTime {
+    /// Writes the date time in RFC 3339 form, or without an offset when it
+    /// has none.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}T{}", self.date, self.time)?;
+        match self.offset_minutes {
+            None => Ok(()),
+            Some(0) => write!(f, "Z"),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                write!(f, "{}{:02}:{:02}", sign, offset / 60, offset % 60)
+            }
+        }
+    }
+}
+
+/// A value read with a [`DateFormat`]: which one depends on the fields of
+/// the format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Temporal {
+    Date(Date),
+    Time(Time),
+    DateTime(DateTime),
+}
+
+impl Temporal {
+    /// The instant this is, as [`Date::to_system_time`] and
+    /// [`DateTime::to_system_time`] give it. `None` for a time of day.
+    pub fn to_system_time(&self) -> Option<SystemTime> {
+        match self {
+            Temporal::Date(date) => Some(date.to_system_time()),
+            Temporal::Time(_) => None,
+            Temporal::DateTime(date_time) => Some(date_time.to_system_time()),
+        }
+    }
+}
+
+impl Display for Temporal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Temporal::Date(date) => date.fmt(f),
+            Temporal::Time(time) => time.fmt(f),
+            Temporal::DateTime(date_time) => date_time.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Date> for chrono::NaiveDate {
+    fn from(date: Date) -> Self {
+        chrono::NaiveDate::from_ymd_opt(date.year, date.month.into(), date.day.into())
+            .expect("dates are validated on creation")
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Time> for chrono::NaiveTime {
+    fn from(time: Time) -> Self {
+        chrono::NaiveTime::from_hms_nano_opt(
+            time.hour.into(),
+            time.minute.into(),
+            time.second.into(),
+            time.nanosecond,
+        )
+        .expect("times are validated on creation")
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<DateTime> for chrono::NaiveDateTime {
+    /// Drops the offset, keeping the local date and time as written.
+    fn from(date_time: DateTime) -> Self {
+        chrono::NaiveDateTime::new(date_time.date.into(), date_time.time.into())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl DateTime {
+    /// The date time with its offset, `None` when it has none.
+    pub fn to_chrono(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        let offset = chrono::FixedOffset::east_opt(i32::from(self.offset_minutes?) * 60)?;
+        chrono::NaiveDateTime::from(*self)
+            .and_local_timezone(offset)
+            .single()
+    }
+}
+
+fn system_time(seconds: i64, nanosecond: u32) -> SystemTime {
+    let since_epoch = Duration::new(seconds.unsigned_abs(), 0);
+    let whole = if seconds < 0 {
+        UNIX_EPOCH - since_epoch
+    } else {
+        UNIX_EPOCH + since_epoch
+    };
+    whole + Duration::from_nanos(nanosecond.into())
+}
+
+fn is_leap_year(year: i32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Parses a run of ASCII digits, rejecting signs and anything else `parse` allows.
+fn digits(text: &str) -> Option<u32> {
+    match text.bytes().all(|byte| byte.is_ascii_digit()) {
+        true => text.parse().ok(),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{Date, DateTime, Time};
+
+    #[test]
+    fn parse_iso_dates() {
+        let date = Date::parse_iso("2024-02-29").unwrap();
+        assert_eq!((date.year(), date.month(), date.day()), (2024, 2, 29));
+        assert_eq!(date.to_string(), "2024-02-29");
+        for text in [
+            "2023-02-29",
+            "2024-13-01",
+            "2024-1-01",
+            "+024-01-01",
+            "2024/01/01",
+        ] {
+            assert!(Date::parse_iso(text).is_none(), "{}", text);
+        }
+        assert!(Date::new(1900, 2, 29).is_none());
+        assert!(Date::new(2000, 2, 29).is_some());
+    }
+
+    #[test]
+    fn convert_to_system_time() {
+        let date = Date::new(2021, 5, 22).unwrap();
+        assert_eq!(
+            date.to_system_time(),
+            UNIX_EPOCH + Duration::from_secs(1_621_641_600)
+        );
+        assert_eq!(
+            Date::new(1969, 12, 31).unwrap().to_system_time(),
+            UNIX_EPOCH - Duration::from_secs(86_400)
+        );
+
+        let time = Time::new(20, 5, 0, 500_000_000).unwrap();
+        let date_time = DateTime::new(date, time, Some(-300)).unwrap();
+        assert_eq!(date_time.to_string(), "2021-05-22T20:05:00.5-05:00");
+        assert_eq!(
+            date_time.to_system_time(),
+            UNIX_EPOCH + Duration::new(1_621_641_600 + 25 * 3600 + 300, 500_000_000)
+        );
+        assert!(Time::new(24, 0, 0, 0).is_none());
+        assert!(DateTime::new(date, time, Some(24 * 60)).is_none());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn convert_to_chrono() {
+        let date = Date::new(2021, 5, 22).unwrap();
+        let time = Time::new(8, 5, 0, 0).unwrap();
+        let date_time = DateTime::new(date, time, Some(120)).unwrap();
+        assert_eq!(
+            date_time.to_chrono().unwrap().to_rfc3339(),
+            "2021-05-22T08:05:00+02:00"
+        );
+        assert_eq!(
+            chrono::NaiveDate::from(date),
+            chrono::NaiveDate::from_ymd_opt(2021, 5, 22).unwrap()
+        );
+        assert!(DateTime::new(date, time, None)
+            .unwrap()
+            .to_chrono()
+            .is_none());
+    }
+}
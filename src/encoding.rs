@@ -0,0 +1,210 @@
+use std::borrow::Cow;
+
+use crate::{ParsingError, ParsonResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+}
+
+/// Works out the encoding of `bytes` and the length of its byte order mark.
+///
+/// Without a BOM the pattern of zero bytes around the first character gives
+/// it away, as the first character of a json or csv text is ASCII
+/// (RFC 8259 §8.1, RFC 4627 §3).
+pub(crate) fn detect(bytes: &[u8]) -> (Encoding, usize) {
+    match bytes {
+        [0x00, 0x00, 0xFE, 0xFF, ..] => (Encoding::Utf32Be, 4),
+        [0xFF, 0xFE, 0x00, 0x00, ..] => (Encoding::Utf32Le, 4),
+        [0xEF, 0xBB, 0xBF, ..] => (Encoding::Utf8, 3),
+        [0xFE, 0xFF, ..] => (Encoding::Utf16Be, 2),
+        [0xFF, 0xFE, ..] => (Encoding::Utf16Le, 2),
+        [0x00, 0x00, 0x00, _, ..] => (Encoding::Utf32Be, 0),
+        [_, 0x00, 0x00, 0x00, ..] => (Encoding::Utf32Le, 0),
+        [0x00, _, ..] => (Encoding::Utf16Be, 0),
+        [_, 0x00, ..] => (Encoding::Utf16Le, 0),
+        _ => (Encoding::Utf8, 0),
+    }
+}
+
+/// Returns `bytes` as UTF-8 without its byte order mark, transcoding UTF-16
+/// and UTF-32 input. UTF-8 input is borrowed, not validated.
+pub(crate) fn to_utf8(bytes: &[u8]) -> ParsonResult<Cow<'_, [u8]>> {
+    let (encoding, bom) = detect(bytes);
+    let bytes = &bytes[bom..];
+    let decoded = match encoding {
+        Encoding::Utf8 => return Ok(Cow::Borrowed(bytes)),
+        Encoding::Utf16Le => decode_utf16(bytes, u16::from_le_bytes)?,
+        Encoding::Utf16Be => decode_utf16(bytes, u16::from_be_bytes)?,
+        Encoding::Utf32Le => decode_utf32(bytes, u32::from_le_bytes)?,
+        Encoding::Utf32Be => decode_utf32(bytes, u32::from_be_bytes)?,
+    };
+    Ok(Cow::Owned(decoded.into_bytes()))
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> ParsonResult<String> {
+    if bytes.len() % 2 != 0 {
+        return Err(ParsingError {
+            message: "utf-16 input has an odd number of bytes".to_string(),
+        });
+    }
+    char::decode_utf16(bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]])))
+        .collect::<Result<String, _>>()
+        .map_err(|error| ParsingError {
+            message: format!("invalid utf-16 input: {}", error),
+        })
+}
+
+fn decode_utf32(bytes: &[u8], unit: fn([u8; 4]) -> u32) -> ParsonResult<String> {
+    if bytes.len() % 4 != 0 {
+        return Err(ParsingError {
+            message: "utf-32 input length is not a multiple of 4 bytes".to_string(),
+        });
+    }
+    bytes
+        .chunks_exact(4)
+        .map(|quad| {
+            let code = unit([quad[0], quad[1], quad[2], quad[3]]);
+            char::from_u32(code).ok_or_else(|| ParsingError {
+                message: format!("invalid utf-32 code point {:#x}", code),
+            })
+        })
+        .collect()
+}
+
+/// Decodes a single-byte code page: ASCII stays as is and `high` maps the
+/// bytes from 0x80 up.
+pub(crate) fn decode_single_byte(bytes: &[u8], high: fn(u8) -> char) -> String {
+    bytes
+        .iter()
+        .map(|byte| match byte {
+            0x00..=0x7F => *byte as char,
+            _ => high(*byte),
+        })
+        .collect()
+}
+
+pub(crate) fn latin1(byte: u8) -> char {
+    byte as char
+}
+
+/// Windows-1252 is Latin-1 with printable characters in place of most of the
+/// C1 controls. The five unassigned bytes keep their Latin-1 meaning.
+pub(crate) fn windows_1252(byte: u8) -> char {
+    const C1: [char; 32] = [
+        '\u{20AC}', '\u{81}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}',
+        '\u{2021}', '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{8D}',
+        '\u{017D}', '\u{8F}', '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}',
+        '\u{2013}', '\u{2014}', '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}',
+        '\u{9D}', '\u{017E}', '\u{0178}',
+    ];
+    match byte {
+        0x80..=0x9F => C1[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
+
+/// ISO-8859-15 (Latin-9) replaces eight Latin-1 characters, adding the euro sign.
+pub(crate) fn iso_8859_15(byte: u8) -> char {
+    match byte {
+        0xA4 => '\u{20AC}',
+        0xA6 => '\u{0160}',
+        0xA8 => '\u{0161}',
+        0xB4 => '\u{017D}',
+        0xB8 => '\u{017E}',
+        0xBC => '\u{0152}',
+        0xBD => '\u{0153}',
+        0xBE => '\u{0178}',
+        _ => byte as char,
+    }
+}
+
+/// Decodes UTF-8, replacing invalid sequences with U+FFFD. Also returns the
+/// byte offsets in the decoded text of the replacements.
+pub(crate) fn decode_lossy(bytes: &[u8]) -> (String, Vec<usize>) {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let mut decoded = String::with_capacity(bytes.len());
+    let mut replaced = vec![];
+    for chunk in bytes.utf8_chunks() {
+        decoded.push_str(chunk.valid());
+        if !chunk.invalid().is_empty() {
+            replaced.push(decoded.len());
+            decoded.push(char::REPLACEMENT_CHARACTER);
+        }
+    }
+    (decoded, replaced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_lossy, decode_single_byte, detect, iso_8859_15, latin1, to_utf8, windows_1252,
+        Encoding,
+    };
+
+    fn encode_utf16(text: &str, big_endian: bool) -> Vec<u8> {
+        text.encode_utf16()
+            .flat_map(|unit| match big_endian {
+                true => unit.to_be_bytes(),
+                false => unit.to_le_bytes(),
+            })
+            .collect()
+    }
+
+    fn encode_utf32(text: &str, big_endian: bool) -> Vec<u8> {
+        text.chars()
+            .flat_map(|character| match big_endian {
+                true => (character as u32).to_be_bytes(),
+                false => (character as u32).to_le_bytes(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detect_boms_and_zero_patterns() {
+        assert_eq!(detect(b"\xEF\xBB\xBF{}"), (Encoding::Utf8, 3));
+        assert_eq!(detect(b"\xFF\xFE{\x00"), (Encoding::Utf16Le, 2));
+        assert_eq!(detect(b"\xFE\xFF\x00{"), (Encoding::Utf16Be, 2));
+        assert_eq!(detect(b"\xFF\xFE\x00\x00"), (Encoding::Utf32Le, 4));
+        assert_eq!(detect(b"\x00\x00\xFE\xFF"), (Encoding::Utf32Be, 4));
+        assert_eq!(detect(b"{\x00\"\x00"), (Encoding::Utf16Le, 0));
+        assert_eq!(detect(b"\x00{\x00\""), (Encoding::Utf16Be, 0));
+        assert_eq!(detect(b"{\x00\x00\x00"), (Encoding::Utf32Le, 0));
+        assert_eq!(detect(b"\x00\x00\x00{"), (Encoding::Utf32Be, 0));
+        assert_eq!(detect(b"{}"), (Encoding::Utf8, 0));
+        assert_eq!(detect(b""), (Encoding::Utf8, 0));
+    }
+
+    #[test]
+    fn transcode_to_utf8() {
+        let text = "{\"name\": \"Zoë 😀\"}";
+        for big_endian in [true, false] {
+            let utf16 = encode_utf16(text, big_endian);
+            assert_eq!(to_utf8(&utf16).unwrap().as_ref(), text.as_bytes());
+            let utf32 = encode_utf32(&format!("\u{feff}{}", text), big_endian);
+            assert_eq!(to_utf8(&utf32).unwrap().as_ref(), text.as_bytes());
+        }
+        assert_eq!(to_utf8(b"\xEF\xBB\xBFa,b").unwrap().as_ref(), b"a,b");
+        assert!(to_utf8(b"{\x00\x00").is_err());
+        assert!(to_utf8(b"\xFF\xFE\x00\xD8").is_err());
+    }
+
+    #[test]
+    fn decode_single_byte_code_pages() {
+        let bytes = b"caf\xE9 \x80\xA4\x93";
+        assert_eq!(decode_single_byte(bytes, latin1), "café \u{80}¤\u{93}");
+        assert_eq!(decode_single_byte(bytes, windows_1252), "café €¤“");
+        assert_eq!(decode_single_byte(bytes, iso_8859_15), "café \u{80}€\u{93}");
+    }
+
+    #[test]
+    fn replace_invalid_utf8_and_report_offsets() {
+        let (decoded, offsets) = decode_lossy(b"\xEF\xBB\xBFa,b\nx\xFF,1\nok,2\n\xC3,3");
+        assert_eq!(decoded, "a,b\nx\u{FFFD},1\nok,2\n\u{FFFD},3");
+        assert_eq!(offsets, vec![5, 16]);
+    }
+}
@@ -0,0 +1,82 @@
+use std::{fs::File, io::Read, ops::Deref, path::Path};
+
+use crate::{ParsingError, ParsonResult};
+
+/// The contents of a file, mapped into memory when possible.
+pub(crate) enum FileBytes {
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+    Read(Vec<u8>),
+}
+
+impl Deref for FileBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            #[cfg(feature = "mmap")]
+            FileBytes::Mapped(map) => map,
+            FileBytes::Read(bytes) => bytes,
+        }
+    }
+}
+
+/// Maps the file at `path` into memory, or reads it whole when it cannot be
+/// mapped: without the `mmap` feature, and for empty files, pipes and other
+/// special files.
+pub(crate) fn read(path: &Path) -> ParsonResult<FileBytes> {
+    let mut file = File::open(path).map_err(|error| file_error(path, error))?;
+    #[cfg(feature = "mmap")]
+    if file
+        .metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.len() > 0)
+    {
+        // SAFETY: the map is only read while parsing; as with any memory map,
+        // another process truncating the file meanwhile is not guarded against.
+        if let Ok(map) = unsafe { memmap2::Mmap::map(&file) } {
+            return Ok(FileBytes::Mapped(map));
+        }
+    }
+    let mut bytes = vec![];
+    file.read_to_end(&mut bytes)
+        .map_err(|error| file_error(path, error))?;
+    Ok(FileBytes::Read(bytes))
+}
+
+/// Prefixes the message of an error with the path of the file it came from.
+pub(crate) fn file_error(path: &Path, error: impl std::fmt::Display) -> ParsingError {
+    ParsingError {
+        message: format!("{}: {}", path.display(), error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::{file_error, read};
+
+    #[test]
+    fn read_files_whole() {
+        let dir = std::env::temp_dir().join(format!("parson-file-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let full = dir.join("full.json");
+        fs::write(&full, b"[1, 2]").unwrap();
+        let empty = dir.join("empty.json");
+        fs::write(&empty, b"").unwrap();
+
+        assert_eq!(&*read(&full).unwrap(), b"[1, 2]");
+        assert_eq!(&*read(&empty).unwrap(), b"");
+        let error = read(&dir.join("missing.json")).err().unwrap();
+        assert!(error
+            .message
+            .starts_with(&format!("{}: ", dir.join("missing.json").display())));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn prefix_errors_with_the_path() {
+        let error = file_error(Path::new("data/a.csv"), "bad row");
+        assert_eq!(error.message, "data/a.csv: bad row");
+    }
+}
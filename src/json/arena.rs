@@ -0,0 +1,333 @@
+use std::{collections::HashMap, ops::Range, str::from_utf8};
+
+use crate::{encoding, ParsonResult};
+
+use super::{
+    invalid_bytes,
+    token::{Number, Tokenizer},
+    tree::{self, TreeBuilder},
+    Value,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Str {
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Null,
+    Boolean(bool),
+    Number(Number),
+    String(Str),
+    Object { start: usize, end: usize },
+    Array { start: usize, end: usize },
+}
+
+/// A parsed json document stored in a handful of flat buffers instead of one
+/// allocation per string, map and vector. Keys are stored once per distinct
+/// key, and dropping the arena frees the whole tree at once.
+///
+/// Values are read through [`ArenaValue`] handles borrowed from the arena.
+#[derive(Debug)]
+pub struct JsonArena {
+    text: String,
+    nodes: Vec<Node>,
+    members: Vec<(Str, usize)>,
+    elements: Vec<usize>,
+    root: usize,
+}
+
+impl JsonArena {
+    pub fn parse(bytes: &[u8]) -> ParsonResult<Self> {
+        let bytes = encoding::to_utf8(bytes)?;
+        let text = from_utf8(&bytes).map_err(|_| invalid_bytes())?;
+        let tokenizer = Tokenizer::new(&mut text.chars())?;
+        let mut builder = ArenaBuilder {
+            arena: JsonArena {
+                text: String::new(),
+                nodes: vec![],
+                members: vec![],
+                elements: vec![],
+                root: 0,
+            },
+            keys: HashMap::new(),
+        };
+        let root = tree::build(&mut tokenizer.tokens.into_iter(), &mut builder)?;
+        let mut arena = builder.arena;
+        arena.root = root;
+        arena.text.shrink_to_fit();
+        arena.nodes.shrink_to_fit();
+        arena.members.shrink_to_fit();
+        arena.elements.shrink_to_fit();
+        Ok(arena)
+    }
+
+    pub fn root(&self) -> ArenaValue<'_> {
+        ArenaValue {
+            arena: self,
+            node: self.root,
+        }
+    }
+
+    fn str(&self, string: Str) -> &str {
+        &self.text[string.start..string.end]
+    }
+}
+
+struct ArenaBuilder {
+    arena: JsonArena,
+    keys: HashMap<Box<str>, Str>,
+}
+
+impl ArenaBuilder {
+    fn push_str(&mut self, string: &str) -> Str {
+        let start = self.arena.text.len();
+        self.arena.text.push_str(string);
+        Str {
+            start,
+            end: self.arena.text.len(),
+        }
+    }
+
+    fn push_node(&mut self, node: Node) -> usize {
+        self.arena.nodes.push(node);
+        self.arena.nodes.len() - 1
+    }
+}
+
+impl TreeBuilder for ArenaBuilder {
+    type Key = Str;
+    type Node = usize;
+
+    fn key(&mut self, key: &str) -> Str {
+        if let Some(string) = self.keys.get(key) {
+            return *string;
+        }
+        let string = self.push_str(key);
+        self.keys.insert(key.into(), string);
+        string
+    }
+
+    fn string(&mut self, string: String) -> usize {
+        let string = self.push_str(&string);
+        self.push_node(Node::String(string))
+    }
+
+    fn number(&mut self, number: Number) -> usize {
+        self.push_node(Node::Number(number))
+    }
+
+    fn boolean(&mut self, boolean: bool) -> usize {
+        self.push_node(Node::Boolean(boolean))
+    }
+
+    fn null(&mut self) -> usize {
+        self.push_node(Node::Null)
+    }
+
+    fn object(&mut self, members: Vec<(Str, usize)>) -> usize {
+        // Keys are interned, so equal keys have equal offsets. The last of a
+        // repeated key wins, like in `Value::Object`.
+        let mut seen = HashMap::new();
+        let start = self.arena.members.len();
+        for (key, node) in members {
+            match seen.get(&key) {
+                Some(idx) => self.arena.members[*idx] = (key, node),
+                None => {
+                    seen.insert(key, self.arena.members.len());
+                    self.arena.members.push((key, node));
+                }
+            }
+        }
+        let end = self.arena.members.len();
+        self.push_node(Node::Object { start, end })
+    }
+
+    fn array(&mut self, elements: Vec<usize>) -> usize {
+        let start = self.arena.elements.len();
+        self.arena.elements.extend(elements);
+        let end = self.arena.elements.len();
+        self.push_node(Node::Array { start, end })
+    }
+}
+
+/// A value inside a [`JsonArena`].
+#[derive(Debug, Clone, Copy)]
+pub struct ArenaValue<'a> {
+    arena: &'a JsonArena,
+    node: usize,
+}
+
+impl<'a> ArenaValue<'a> {
+    fn with(&self, node: usize) -> ArenaValue<'a> {
+        ArenaValue {
+            arena: self.arena,
+            node,
+        }
+    }
+
+    fn node(&self) -> &'a Node {
+        &self.arena.nodes[self.node]
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self.node(), Node::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.node() {
+            Node::Boolean(boolean) => Some(*boolean),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.node() {
+            Node::Number(Number::Float(number)) => Some(*number),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self.node() {
+            Node::String(string) => Some(self.arena.str(*string)),
+            _ => None,
+        }
+    }
+
+    pub fn is_object(&self) -> bool {
+        matches!(self.node(), Node::Object { .. })
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self.node(), Node::Array { .. })
+    }
+
+    /// Number of members or elements; 0 for scalars.
+    pub fn len(&self) -> usize {
+        match self.node() {
+            Node::Object { start, end } | Node::Array { start, end } => end - start,
+            _ => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The member named `key` of an object.
+    pub fn get(&self, key: &str) -> Option<ArenaValue<'a>> {
+        self.members()
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| value)
+    }
+
+    /// The element at `idx` of an array.
+    pub fn at(&self, idx: usize) -> Option<ArenaValue<'a>> {
+        self.elements().nth(idx)
+    }
+
+    /// The members of an object in document order, empty for anything else.
+    pub fn members(&self) -> impl Iterator<Item = (&'a str, ArenaValue<'a>)> + 'a {
+        let range = match self.node() {
+            Node::Object { start, end } => *start..*end,
+            _ => 0..0,
+        };
+        let value = *self;
+        self.arena.members[range]
+            .iter()
+            .map(move |(key, node)| (value.arena.str(*key), value.with(*node)))
+    }
+
+    /// The elements of an array, empty for anything else.
+    pub fn elements(&self) -> impl Iterator<Item = ArenaValue<'a>> + 'a {
+        let range: Range<usize> = match self.node() {
+            Node::Array { start, end } => *start..*end,
+            _ => 0..0,
+        };
+        let value = *self;
+        self.arena.elements[range]
+            .iter()
+            .map(move |node| value.with(*node))
+    }
+
+    /// Copies the value into a plain [`Value`].
+    pub fn to_value(&self) -> Value {
+        match self.node() {
+            Node::Null => Value::Null,
+            Node::Boolean(boolean) => Value::Boolean(*boolean),
+            Node::Number(number) => Value::Number(number.clone()),
+            Node::String(string) => Value::String(self.arena.str(*string).to_string()),
+            Node::Object { .. } => Value::Object(
+                self.members()
+                    .map(|(key, value)| (key.to_string(), value.to_value()))
+                    .collect(),
+            ),
+            Node::Array { .. } => {
+                Value::Array(self.elements().map(|value| value.to_value()).collect())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::JsonParser;
+
+    use super::JsonArena;
+
+    #[test]
+    fn navigate_the_tree() {
+        let text =
+            r#"[{"name": "Zoë", "age": 31, "tags": ["a", "b"], "admin": false, "boss": null}]"#;
+        let arena = JsonArena::parse(text.as_bytes()).unwrap();
+        let root = arena.root();
+        assert!(root.is_array());
+        assert_eq!(root.len(), 1);
+        let person = root.at(0).unwrap();
+        assert!(person.is_object());
+        assert_eq!(person.get("name").unwrap().as_str(), Some("Zoë"));
+        assert_eq!(person.get("age").unwrap().as_f64(), Some(31.0));
+        assert_eq!(person.get("admin").unwrap().as_bool(), Some(false));
+        assert!(person.get("boss").unwrap().is_null());
+        assert!(person.get("missing").is_none());
+        let tags = person.get("tags").unwrap();
+        assert_eq!(
+            tags.elements()
+                .filter_map(|tag| tag.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            person.members().map(|(key, _)| key).collect::<Vec<_>>(),
+            vec!["name", "age", "tags", "admin", "boss"]
+        );
+        assert!(root.at(1).is_none());
+    }
+
+    #[test]
+    fn store_each_key_once() {
+        let arena = JsonArena::parse(br#"[{"key": "v"}, {"key": "w"}, {"key": "key"}]"#).unwrap();
+        assert_eq!(arena.text.len(), "keyvwkey".len());
+    }
+
+    #[test]
+    fn match_the_plain_parse() {
+        for text in [
+            r#"{"a": {"b": [1, null, true]}, "a": 2, "c": "d"}"#,
+            "12",
+            "[1,,2]",
+            "[]",
+        ] {
+            let plain = JsonParser::new(text.as_bytes()).unwrap().parse().unwrap();
+            let arena = JsonArena::parse(text.as_bytes()).unwrap();
+            assert_eq!(arena.root().to_value(), plain);
+        }
+        let arena = JsonArena::parse(br#"{"a": 1, "b": 2, "a": 3}"#).unwrap();
+        assert_eq!(arena.root().len(), 2);
+        assert_eq!(arena.root().get("a").unwrap().as_f64(), Some(3.0));
+        assert!(JsonArena::parse(b"}").is_err());
+    }
+}
@@ -0,0 +1,232 @@
+use std::{fmt::Write, hash::Hasher};
+
+use crate::{ParsingError, ParsonResult};
+
+use super::{token::Number, value::write_escaped, Value};
+
+impl Value {
+    /// Serializes the value following RFC 8785 (JSON Canonicalization Scheme).
+    ///
+    /// Object keys are sorted by their UTF-16 code units, numbers use the
+    /// ECMAScript formatting and strings only escape what they must, so equal
+    /// values always give the same bytes. Fails on NaN and infinite numbers.
+    pub fn to_canonical_string(&self) -> ParsonResult<String> {
+        let mut canonical = String::new();
+        write_value(self, &mut canonical)?;
+        Ok(canonical)
+    }
+
+    /// Feeds the canonical bytes of the value into `hasher`.
+    pub fn canonical_hash<H: Hasher>(&self, hasher: &mut H) -> ParsonResult<()> {
+        self.canonical_digest(|bytes| hasher.write(bytes))
+    }
+
+    /// Feeds the canonical bytes of the value into a user supplied digest,
+    /// e.g. `value.canonical_digest(|bytes| sha.update(bytes))`.
+    pub fn canonical_digest(&self, mut update: impl FnMut(&[u8])) -> ParsonResult<()> {
+        update(self.to_canonical_string()?.as_bytes());
+        Ok(())
+    }
+}
+
+fn write_value(value: &Value, out: &mut String) -> ParsonResult<()> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Boolean(boolean) => out.push_str(if *boolean { "true" } else { "false" }),
+        Value::Number(Number::Float(number)) => write_number(*number, out)?,
+        Value::String(string) => {
+            let _ = write_escaped(string, out);
+        }
+        Value::Array(array) => {
+            out.push('[');
+            for (idx, item) in array.iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                write_value(item, out)?;
+            }
+            out.push(']');
+        }
+        Value::Object(object) => {
+            let mut entries = object.iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (idx, (key, item)) in entries.into_iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                let _ = write_escaped(key, out);
+                out.push(':');
+                write_value(item, out)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+/// Formats a number the way ECMAScript's `Number.prototype.toString` does.
+fn write_number(number: f64, out: &mut String) -> ParsonResult<()> {
+    if !number.is_finite() {
+        return Err(ParsingError {
+            message: format!("{} cannot be represented in canonical json", number),
+        });
+    }
+    if number == 0.0 {
+        out.push('0');
+        return Ok(());
+    }
+    if number < 0.0 {
+        out.push('-');
+    }
+
+    // rust already gives the shortest round-tripping digits, only the layout differs
+    let scientific = format!("{:e}", number.abs());
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .expect("exponent formatting always contains an e");
+    let digits = mantissa.replace('.', "");
+    let exponent: i32 = exponent.parse().expect("exponent is always an integer");
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.push_str(&"0".repeat((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.push_str(&"0".repeat(-n as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        let _ = write!(out, "e{}{}", if n > 0 { "+" } else { "-" }, (n - 1).abs());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::hash_map::DefaultHasher, collections::HashMap, hash::Hasher};
+
+    use crate::{
+        json::{token::Number, Value},
+        Parson,
+    };
+
+    fn canonical_number(number: f64) -> String {
+        Value::Number(Number::Float(number))
+            .to_canonical_string()
+            .unwrap()
+    }
+
+    #[test]
+    fn format_numbers_like_ecmascript() {
+        let cases = [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (4.5, "4.5"),
+            (0.002, "0.002"),
+            (0.000001, "0.000001"),
+            (1e-7, "1e-7"),
+            (-1.5e-7, "-1.5e-7"),
+            (333333333.3333333, "333333333.3333333"),
+            (1e20, "100000000000000000000"),
+            (1e21, "1e+21"),
+            (1e30, "1e+30"),
+            (9007199254740992.0, "9007199254740992"),
+            (5e-324, "5e-324"),
+            (1.7976931348623157e308, "1.7976931348623157e+308"),
+        ];
+        for (number, expected) in cases {
+            assert_eq!(canonical_number(number), expected);
+        }
+        assert!(Value::Number(Number::Float(f64::NAN))
+            .to_canonical_string()
+            .is_err());
+    }
+
+    #[test]
+    fn sort_keys_by_utf16_code_units() {
+        let keys = [
+            "\u{20ac}",
+            "\r",
+            "\u{fb33}",
+            "1",
+            "\u{1f600}",
+            "\u{80}",
+            "\u{f6}",
+        ];
+        let value = Value::Object(
+            keys.iter()
+                .map(|key| (key.to_string(), Value::Null))
+                .collect::<HashMap<String, Value>>(),
+        );
+        assert_eq!(
+            value.to_canonical_string().unwrap(),
+            "{\"\\r\":null,\"1\":null,\"\u{80}\":null,\"\u{f6}\":null,\"\u{20ac}\":null,\"\u{1f600}\":null,\"\u{fb33}\":null}"
+        );
+    }
+
+    #[test]
+    fn escape_only_what_is_needed() {
+        let value = Value::Array(vec![
+            Value::String("a\"b\\c/d\u{1}\n€".to_string()),
+            Value::Boolean(true),
+        ]);
+        assert_eq!(
+            value.to_canonical_string().unwrap(),
+            "[\"a\\\"b\\\\c/d\\u0001\\n€\",true]"
+        );
+    }
+
+    #[test]
+    fn hash_ignores_key_order() {
+        let first = Value::Object(HashMap::from([
+            ("a".to_string(), Value::Null),
+            ("b".to_string(), Value::Boolean(false)),
+        ]));
+        let second = Value::Object(HashMap::from([
+            ("b".to_string(), Value::Boolean(false)),
+            ("a".to_string(), Value::Null),
+        ]));
+
+        let mut first_hasher = DefaultHasher::new();
+        first.canonical_hash(&mut first_hasher).unwrap();
+        let mut second_hasher = DefaultHasher::new();
+        second.canonical_hash(&mut second_hasher).unwrap();
+        assert_eq!(first_hasher.finish(), second_hasher.finish());
+
+        let mut digested = vec![];
+        first
+            .canonical_digest(|bytes| digested.extend_from_slice(bytes))
+            .unwrap();
+        assert_eq!(digested, br#"{"a":null,"b":false}"#);
+    }
+
+    #[test]
+    fn canonicalize_parsed_escapes() {
+        let parsed = Parson::parse_json(r#"{"a":"x\ny","b":"\u00e9\"\\"}"#).unwrap();
+        assert_eq!(
+            parsed.to_canonical_string().unwrap(),
+            r#"{"a":"x\ny","b":"é\"\\"}"#
+        );
+
+        let hash = |text| {
+            let mut hasher = DefaultHasher::new();
+            Parson::parse_json(text)
+                .unwrap()
+                .canonical_hash(&mut hasher)
+                .unwrap();
+            hasher.finish()
+        };
+        assert_eq!(hash(r#""\u00e9""#), hash(r#""é""#));
+    }
+}
@@ -0,0 +1,652 @@
+use std::fmt;
+
+use crate::{ParsingError, ParsonResult};
+
+use super::{
+    lexer::{decode_string, Lexeme, Lexer, Span, TokenKind},
+    value::write_escaped,
+    JsonPointer, Value,
+};
+
+/// A json document that keeps its source text around, so it can be edited
+/// without losing whitespace, comments, key order or number formatting.
+///
+/// `//` and `/* */` comments and trailing commas are accepted (JSONC). Edits
+/// only rewrite the region they touch and printing the document gives back
+/// every other byte unchanged. Inserted values are written in compact form,
+/// indented like their neighbours.
+#[derive(Debug, Clone)]
+pub struct JsonDocument {
+    source: String,
+    root: Node,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    start: usize,
+    end: usize,
+    kind: NodeKind,
+}
+
+#[derive(Debug, Clone)]
+enum NodeKind {
+    Scalar,
+    Object(Vec<Item>),
+    Array(Vec<Item>),
+}
+
+/// An object member or array element. `start` is where the key, or the
+/// element, begins and `comma` the offset of the comma following it.
+#[derive(Debug, Clone)]
+struct Item {
+    key: Option<Key>,
+    start: usize,
+    value: Node,
+    comma: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct Key {
+    name: String,
+    end: usize,
+}
+
+impl JsonDocument {
+    pub fn parse(source: &str) -> ParsonResult<Self> {
+        let mut parser = CstParser {
+            source,
+            lexemes: Lexer::new(source)
+                .filter(|lexeme| !matches!(lexeme.kind, TokenKind::Whitespace | TokenKind::Comment))
+                .collect(),
+            pos: 0,
+        };
+        let root = parser.parse_value()?;
+        if let Some(lexeme) = parser.lexemes.get(parser.pos) {
+            return Err(error(lexeme.span, "unexpected data after the json value"));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            root,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the source text of the value at `pointer`, exactly as written.
+    pub fn get(&self, pointer: &str) -> ParsonResult<Option<&str>> {
+        let pointer = JsonPointer::parse(pointer)?;
+        Ok(self
+            .node(pointer.segments())
+            .map(|node| &self.source[node.start..node.end]))
+    }
+
+    /// Replaces the value at `pointer`, or adds it when the parent object
+    /// has no such member yet.
+    pub fn set(&mut self, pointer: &str, value: &Value) -> ParsonResult<()> {
+        let pointer = JsonPointer::parse(pointer)?;
+        let Some(node) = self.node(pointer.segments()) else {
+            return self.insert_at(&pointer, value);
+        };
+        let (start, end) = (node.start, node.end);
+        let text = value.to_string();
+        let replacement = parse_node(&text, start)?;
+        self.splice(start, end, &text);
+        *node_mut(&mut self.root, pointer.segments()).expect("the node was found before") =
+            replacement;
+        Ok(())
+    }
+
+    /// Adds a member to an object, or an element to an array at the given
+    /// index (`-` appends). Fails if the object member already exists.
+    pub fn insert(&mut self, pointer: &str, value: &Value) -> ParsonResult<()> {
+        let pointer = JsonPointer::parse(pointer)?;
+        self.insert_at(&pointer, value)
+    }
+
+    /// Removes the member or element at `pointer` along with one separator.
+    /// Comments and blank lines around it are kept.
+    pub fn remove(&mut self, pointer: &str) -> ParsonResult<()> {
+        let pointer = JsonPointer::parse(pointer)?;
+        let (parent, last) = self.parent(&pointer)?;
+        let items = match &parent.kind {
+            NodeKind::Object(items) | NodeKind::Array(items) => items,
+            NodeKind::Scalar => return Err(missing(&pointer)),
+        };
+        let idx = find_item(parent, last).ok_or_else(|| missing(&pointer))?;
+        let (start, end) = self.item_range(&items[idx]);
+        // Without a comma of its own, the last item takes the one before it.
+        let comma_before = match (items[idx].comma, idx) {
+            (None, 1..) => items[idx - 1].comma,
+            _ => None,
+        };
+
+        self.splice(start, end, "");
+        if let Some(comma) = comma_before {
+            self.splice(comma, comma + 1, "");
+        }
+        let items = self.items_mut(&pointer);
+        items.remove(idx);
+        if comma_before.is_some() {
+            items[idx - 1].comma = None;
+        }
+        Ok(())
+    }
+
+    fn insert_at(&mut self, pointer: &JsonPointer, value: &Value) -> ParsonResult<()> {
+        let (parent, last) = self.parent(pointer)?;
+        let value = value.to_string();
+        let (items, key) = match &parent.kind {
+            NodeKind::Object(items) => {
+                if find_item(parent, last).is_some() {
+                    return Err(ParsingError {
+                        message: format!("{} already exists", pointer),
+                    });
+                }
+                let separator = items
+                    .last()
+                    .and_then(|item| {
+                        let key = item.key.as_ref()?;
+                        Some(&self.source[key.end..item.value.start])
+                    })
+                    .filter(|separator| separator.trim() == ":")
+                    .unwrap_or(": ");
+                let mut key = String::new();
+                let _ = write_escaped(last, &mut key);
+                (items, Some((key, separator.to_string())))
+            }
+            NodeKind::Array(items) => {
+                let idx = match last {
+                    "-" => items.len(),
+                    _ => last
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|idx| *idx <= items.len())
+                        .ok_or_else(|| missing(pointer))?,
+                };
+                if idx < items.len() {
+                    let indent = self.indent(parent, items, if idx == 0 { 1 } else { idx });
+                    let at = items[idx].start;
+                    let text = format!("{},{}", value, indent);
+                    let item = Item {
+                        key: None,
+                        start: at,
+                        value: parse_node(&value, at)?,
+                        comma: Some(at + value.len()),
+                    };
+                    self.splice(at, at, &text);
+                    self.items_mut(pointer).insert(idx, item);
+                    return Ok(());
+                }
+                (items, None)
+            }
+            NodeKind::Scalar => return Err(missing(pointer)),
+        };
+
+        // Where the text goes, what comes before the item in it and whether
+        // the current last item gets a comma.
+        let (at, before, comma_before, comma_after) = match items.last() {
+            None => (parent.start + 1, String::new(), false, false),
+            Some(item) => {
+                let indent = self.indent(parent, items, items.len() - 1).to_string();
+                match item.comma {
+                    Some(comma) => (comma + 1, indent, false, true),
+                    None => (item.value.end, format!(",{}", indent), true, false),
+                }
+            }
+        };
+        let start = at + before.len();
+        let (key, value_start) = match key {
+            Some((escaped, separator)) => {
+                let end = start + escaped.len();
+                let key = Key {
+                    name: last.to_string(),
+                    end,
+                };
+                (Some((key, escaped + &separator)), end + separator.len())
+            }
+            None => (None, start),
+        };
+        let mut text = before;
+        if let Some((_, key_text)) = &key {
+            text.push_str(key_text);
+        }
+        text.push_str(&value);
+        let item = Item {
+            key: key.map(|(key, _)| key),
+            start,
+            value: parse_node(&value, value_start)?,
+            comma: comma_after.then_some(value_start + value.len()),
+        };
+        if comma_after {
+            text.push(',');
+        }
+
+        self.splice(at, at, &text);
+        let items = self.items_mut(pointer);
+        if comma_before {
+            if let Some(last) = items.last_mut() {
+                last.comma = Some(at);
+            }
+        }
+        items.push(item);
+        Ok(())
+    }
+
+    /// The range an item and its own comma take up: the whole lines when
+    /// nothing else is on them, or else the item with the blanks that
+    /// separate it from the next one, or from the previous one for the last.
+    fn item_range(&self, item: &Item) -> (usize, usize) {
+        let end = item.comma.map_or(item.value.end, |comma| comma + 1);
+        let before = &self.source[..item.start];
+        let line_start = before.trim_end_matches([' ', '\t']).len();
+        let after = &self.source[end..];
+        let blanks = after.len() - after.trim_start_matches([' ', '\t']).len();
+        let newline = ["\n", "\r\n"]
+            .into_iter()
+            .find(|newline| after[blanks..].starts_with(newline));
+        match newline {
+            Some(newline) if before[..line_start].ends_with('\n') => {
+                (line_start, end + blanks + newline.len())
+            }
+            _ if item.comma.is_some() => (item.start, end + blanks),
+            _ => (line_start, end),
+        }
+    }
+
+    /// The whitespace that precedes the item at `idx` (or the last item when
+    /// there are fewer), comments left out. A lone item on one line has no
+    /// separator to copy, so items after it are set off by a space.
+    fn indent(&self, container: &Node, items: &[Item], idx: usize) -> &str {
+        let idx = idx.min(items.len() - 1);
+        let trivia_start = match idx {
+            0 => container.start + 1,
+            _ => items[idx - 1]
+                .comma
+                .map_or(items[idx].start, |comma| comma + 1),
+        };
+        let trivia = &self.source[trivia_start..items[idx].start];
+        match trivia.rfind('\n') {
+            Some(newline) if trivia[..newline].ends_with('\r') => &trivia[newline - 1..],
+            Some(newline) => &trivia[newline..],
+            None if idx == 0 => " ",
+            None => &trivia[trivia.trim_end().len()..],
+        }
+    }
+
+    fn node(&self, segments: &[String]) -> Option<&Node> {
+        segments.iter().try_fold(&self.root, |node, segment| {
+            let idx = find_item(node, segment)?;
+            match &node.kind {
+                NodeKind::Object(items) | NodeKind::Array(items) => Some(&items[idx].value),
+                NodeKind::Scalar => None,
+            }
+        })
+    }
+
+    fn parent<'a>(&self, pointer: &'a JsonPointer) -> ParsonResult<(&Node, &'a str)> {
+        let (last, parents) = pointer
+            .segments()
+            .split_last()
+            .ok_or_else(|| ParsingError {
+                message: "the document root has no parent".to_string(),
+            })?;
+        let parent = self.node(parents).ok_or_else(|| missing(pointer))?;
+        Ok((parent, last))
+    }
+
+    /// The items of the parent of `pointer`, which [`Self::parent`] has
+    /// already found to be a container.
+    fn items_mut(&mut self, pointer: &JsonPointer) -> &mut Vec<Item> {
+        let parents = &pointer.segments()[..pointer.segments().len() - 1];
+        match node_mut(&mut self.root, parents).map(|node| &mut node.kind) {
+            Some(NodeKind::Object(items) | NodeKind::Array(items)) => items,
+            _ => unreachable!("the parent was found before"),
+        }
+    }
+
+    /// Replaces `start..end` of the source with `text` and moves the offsets
+    /// that come after it. Nodes inside the range are left for the caller to
+    /// replace or drop.
+    fn splice(&mut self, start: usize, end: usize, text: &str) {
+        self.source.replace_range(start..end, text);
+        let delta = text.len() as isize - (end - start) as isize;
+        self.root.shift(start, end, delta);
+    }
+}
+
+impl Node {
+    /// Moves the offsets at or after `end` by `delta`. An offset that ends a
+    /// span stays put when text is inserted right after it.
+    fn shift(&mut self, start: usize, end: usize, delta: isize) {
+        let moved = |offset: usize, is_end: bool| match offset >= end && (!is_end || offset > start)
+        {
+            true => offset.wrapping_add_signed(delta),
+            false => offset,
+        };
+        self.start = moved(self.start, false);
+        self.end = moved(self.end, true);
+        if let NodeKind::Object(items) | NodeKind::Array(items) = &mut self.kind {
+            for item in items {
+                item.start = moved(item.start, false);
+                item.comma = item.comma.map(|comma| moved(comma, false));
+                if let Some(key) = &mut item.key {
+                    key.end = moved(key.end, true);
+                }
+                item.value.shift(start, end, delta);
+            }
+        }
+    }
+}
+
+/// Parses the compact text of an inserted value, placed at `at`.
+fn parse_node(text: &str, at: usize) -> ParsonResult<Node> {
+    let mut node = JsonDocument::parse(text)?.root;
+    node.shift(0, 0, at as isize);
+    Ok(node)
+}
+
+fn node_mut<'a>(root: &'a mut Node, segments: &[String]) -> Option<&'a mut Node> {
+    segments.iter().try_fold(root, |node, segment| {
+        let idx = find_item(node, segment)?;
+        match &mut node.kind {
+            NodeKind::Object(items) | NodeKind::Array(items) => Some(&mut items[idx].value),
+            NodeKind::Scalar => None,
+        }
+    })
+}
+
+impl fmt::Display for JsonDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn find_item(node: &Node, segment: &str) -> Option<usize> {
+    match &node.kind {
+        NodeKind::Object(items) => items
+            .iter()
+            .rposition(|item| item.key.as_ref().is_some_and(|key| key.name == segment)),
+        NodeKind::Array(items) => segment
+            .parse::<usize>()
+            .ok()
+            .filter(|idx| *idx < items.len()),
+        NodeKind::Scalar => None,
+    }
+}
+
+fn missing(pointer: &JsonPointer) -> ParsingError {
+    ParsingError {
+        message: format!("no value at {}", pointer),
+    }
+}
+
+fn error(span: Span, message: &str) -> ParsingError {
+    ParsingError {
+        message: format!("line {}, column {}: {}", span.line, span.column, message),
+    }
+}
+
+struct CstParser<'a> {
+    source: &'a str,
+    lexemes: Vec<Lexeme>,
+    pos: usize,
+}
+
+impl CstParser<'_> {
+    fn next(&mut self, expected: &str) -> ParsonResult<Lexeme> {
+        let lexeme = self
+            .lexemes
+            .get(self.pos)
+            .copied()
+            .ok_or_else(|| ParsingError {
+                message: format!("unexpected end of input, expected {}", expected),
+            })?;
+        self.pos += 1;
+        Ok(lexeme)
+    }
+
+    fn expect(&mut self, kind: TokenKind, expected: &str) -> ParsonResult<Lexeme> {
+        let lexeme = self.next(expected)?;
+        if lexeme.kind != kind {
+            return Err(error(lexeme.span, &format!("expected {}", expected)));
+        }
+        Ok(lexeme)
+    }
+
+    fn parse_value(&mut self) -> ParsonResult<Node> {
+        let lexeme = self.next("a value")?;
+        let kind = match lexeme.kind {
+            TokenKind::OpenCurlyBracket => {
+                return self.parse_items(lexeme, TokenKind::CloseCurlyBracket)
+            }
+            TokenKind::OpenSquareBracket => {
+                return self.parse_items(lexeme, TokenKind::CloseSquareBracket)
+            }
+            TokenKind::String => {
+                let raw = &self.source[lexeme.span.start..lexeme.span.end];
+                decode_string(raw).map_err(|message| error(lexeme.span, &message))?;
+                NodeKind::Scalar
+            }
+            TokenKind::Number | TokenKind::Boolean | TokenKind::Null => NodeKind::Scalar,
+            _ => return Err(error(lexeme.span, "expected a value")),
+        };
+        Ok(Node {
+            start: lexeme.span.start,
+            end: lexeme.span.end,
+            kind,
+        })
+    }
+
+    fn parse_items(&mut self, open: Lexeme, close: TokenKind) -> ParsonResult<Node> {
+        let is_object = close == TokenKind::CloseCurlyBracket;
+        let mut items = vec![];
+        loop {
+            if self.lexemes.get(self.pos).is_some_and(|l| l.kind == close) {
+                let end = self.next("a closing bracket")?.span.end;
+                return Ok(container(open, end, is_object, items));
+            }
+            let key = if is_object {
+                let lexeme = self.expect(TokenKind::String, "an object key")?;
+                let raw = &self.source[lexeme.span.start..lexeme.span.end];
+                let name = decode_string(raw).map_err(|message| error(lexeme.span, &message))?;
+                self.expect(TokenKind::Colon, "':'")?;
+                Some((
+                    lexeme.span.start,
+                    Key {
+                        name,
+                        end: lexeme.span.end,
+                    },
+                ))
+            } else {
+                None
+            };
+            let value = self.parse_value()?;
+            let mut item = Item {
+                start: key.as_ref().map_or(value.start, |(start, _)| *start),
+                key: key.map(|(_, key)| key),
+                value,
+                comma: None,
+            };
+            let separator = self.next("',' or a closing bracket")?;
+            match separator.kind {
+                TokenKind::Comma => {
+                    item.comma = Some(separator.span.start);
+                    items.push(item);
+                }
+                kind if kind == close => {
+                    items.push(item);
+                    return Ok(container(open, separator.span.end, is_object, items));
+                }
+                _ => return Err(error(separator.span, "expected ',' or a closing bracket")),
+            }
+        }
+    }
+}
+
+fn container(open: Lexeme, end: usize, is_object: bool, items: Vec<Item>) -> Node {
+    Node {
+        start: open.span.start,
+        end,
+        kind: if is_object {
+            NodeKind::Object(items)
+        } else {
+            NodeKind::Array(items)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JsonDocument;
+
+    /// Checks that the spans updated by the edits match a fresh parse.
+    fn assert_spans(document: &JsonDocument) {
+        let reparsed = JsonDocument::parse(document.as_str()).unwrap();
+        assert_eq!(
+            format!("{:?}", document.root),
+            format!("{:?}", reparsed.root)
+        );
+    }
+
+    const CONFIG: &str = "// editor settings\n{\n  \"theme\": \"dark\", // for the night\n  /* sizes */\n  \"size\": 1.50,\n  \"plugins\": [\"a\", \"b\"],\n}\n";
+
+    #[test]
+    fn print_unchanged_source() {
+        let document = JsonDocument::parse(CONFIG).unwrap();
+        assert_eq!(document.to_string(), CONFIG);
+        assert_eq!(document.get("/size").unwrap(), Some("1.50"));
+        assert_eq!(document.get("/plugins/1").unwrap(), Some("\"b\""));
+        assert_eq!(document.get("/missing").unwrap(), None);
+    }
+
+    #[test]
+    fn set_rewrites_only_the_value() {
+        let mut document = JsonDocument::parse(CONFIG).unwrap();
+        document.set("/theme", &crate::json!("light")).unwrap();
+        assert_eq!(document.as_str(), CONFIG.replace("\"dark\"", "\"light\""));
+
+        document
+            .set("/plugins/0", &crate::json!({"x": [1]}))
+            .unwrap();
+        assert!(document.as_str().contains("[{\"x\":[1]}, \"b\"]"));
+        assert_spans(&document);
+    }
+
+    #[test]
+    fn insert_follows_neighbour_indentation() {
+        let mut document = JsonDocument::parse("{\n  \"a\": 1,\n  \"b\": 2\n}").unwrap();
+        document.insert("/c", &crate::json!(true)).unwrap();
+        assert_eq!(
+            document.as_str(),
+            "{\n  \"a\": 1,\n  \"b\": 2,\n  \"c\": true\n}"
+        );
+        assert!(document.insert("/c", &crate::json!(false)).is_err());
+
+        let mut document = JsonDocument::parse(CONFIG).unwrap();
+        document.set("/new", &crate::json!(null)).unwrap();
+        assert!(document
+            .as_str()
+            .ends_with("\"plugins\": [\"a\", \"b\"],\n  \"new\": null,\n}\n"));
+
+        let mut document = JsonDocument::parse("[1, 2]").unwrap();
+        document.insert("/0", &crate::json!(0)).unwrap();
+        document.insert("/-", &crate::json!(3)).unwrap();
+        document.insert("/2", &crate::json!(1.5)).unwrap();
+        assert_eq!(document.as_str(), "[0, 1, 1.5, 2, 3]");
+        assert_spans(&document);
+        assert!(document.insert("/9", &crate::json!(0)).is_err());
+
+        let mut document = JsonDocument::parse("{}").unwrap();
+        document.insert("/a", &crate::json!([])).unwrap();
+        assert_eq!(document.as_str(), "{\"a\": []}");
+    }
+
+    #[test]
+    fn remove_takes_the_separator_along() {
+        let mut document =
+            JsonDocument::parse("{\n  \"a\": 1,\n  \"b\": 2,\n  \"c\": 3\n}").unwrap();
+        document.remove("/b").unwrap();
+        assert_eq!(document.as_str(), "{\n  \"a\": 1,\n  \"c\": 3\n}");
+        document.remove("/c").unwrap();
+        assert_eq!(document.as_str(), "{\n  \"a\": 1\n}");
+        document.remove("/a").unwrap();
+        assert_eq!(document.as_str(), "{\n}");
+
+        let mut document = JsonDocument::parse("[1, 2, 3]").unwrap();
+        document.remove("/0").unwrap();
+        assert_eq!(document.as_str(), "[2, 3]");
+        document.remove("/1").unwrap();
+        assert_eq!(document.as_str(), "[2]");
+        assert_spans(&document);
+        assert!(document.remove("/5").is_err());
+        assert!(document.remove("").is_err());
+    }
+
+    #[test]
+    fn remove_keeps_the_comments_of_neighbours() {
+        let mut document = JsonDocument::parse("{\"a\": 1, // keep me\n \"b\": 2}").unwrap();
+        document.remove("/b").unwrap();
+        assert_eq!(document.as_str(), "{\"a\": 1 // keep me\n}");
+        assert_spans(&document);
+
+        let mut document = JsonDocument::parse(CONFIG).unwrap();
+        document.remove("/theme").unwrap();
+        assert_eq!(
+            document.as_str(),
+            CONFIG.replace("\"theme\": \"dark\", ", "")
+        );
+        document.remove("/plugins").unwrap();
+        assert_eq!(
+            document.as_str(),
+            "// editor settings\n{\n  // for the night\n  /* sizes */\n  \"size\": 1.50,\n}\n"
+        );
+        assert_spans(&document);
+    }
+
+    #[test]
+    fn keep_spans_in_step_with_edits() {
+        let mut document = JsonDocument::parse(CONFIG).unwrap();
+        document
+            .set("/theme", &crate::json!({"name": "light"}))
+            .unwrap();
+        document
+            .set("/theme/name", &crate::json!("solarized"))
+            .unwrap();
+        document.insert("/plugins/0", &crate::json!([0])).unwrap();
+        document.insert("/plugins/-", &crate::json!("c")).unwrap();
+        document.insert("/extra", &crate::json!(1)).unwrap();
+        document.remove("/size").unwrap();
+        assert_spans(&document);
+        assert_eq!(document.get("/plugins/3").unwrap(), Some("\"c\""));
+        assert_eq!(
+            document.get("/theme").unwrap(),
+            Some("{\"name\":\"solarized\"}")
+        );
+
+        let mut document = JsonDocument::parse("{}").unwrap();
+        document.insert("/a", &crate::json!(1)).unwrap();
+        document.insert("/b", &crate::json!(2)).unwrap();
+        assert_eq!(document.as_str(), "{\"a\": 1, \"b\": 2}");
+        assert_spans(&document);
+
+        let mut document = JsonDocument::parse("[1]").unwrap();
+        document.insert("/0", &crate::json!(0)).unwrap();
+        document.insert("/-", &crate::json!(2)).unwrap();
+        assert_eq!(document.as_str(), "[0, 1, 2]");
+
+        let mut document = JsonDocument::parse("{\"a\":1,\"b\":2}").unwrap();
+        document.insert("/c", &crate::json!(3)).unwrap();
+        assert_eq!(document.as_str(), "{\"a\":1,\"b\":2,\"c\":3}");
+        assert_spans(&document);
+    }
+
+    #[test]
+    fn reject_invalid_documents() {
+        let error = JsonDocument::parse("{\n  \"a\" 1\n}").unwrap_err();
+        assert_eq!(error.message, "line 2, column 7: expected ':'");
+        assert!(JsonDocument::parse("[1] 2").is_err());
+        assert!(JsonDocument::parse("/* open [1]").is_err());
+    }
+}
@@ -0,0 +1,302 @@
+use std::fmt::Write;
+
+use crate::ParsonResult;
+
+use super::{token::Number, JsonPointer, Value};
+
+/// A node that differs between two json values, located by its JSON Pointer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    Added {
+        pointer: JsonPointer,
+        value: Value,
+    },
+    Removed {
+        pointer: JsonPointer,
+        value: Value,
+    },
+    Changed {
+        pointer: JsonPointer,
+        old: Value,
+        new: Value,
+    },
+}
+
+impl Difference {
+    pub fn pointer(&self) -> &JsonPointer {
+        match self {
+            Difference::Added { pointer, .. }
+            | Difference::Removed { pointer, .. }
+            | Difference::Changed { pointer, .. } => pointer,
+        }
+    }
+}
+
+/// Tunes what [`Value::diff`] considers a difference.
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    ignore_array_order: bool,
+    ignored_paths: Vec<JsonPointer>,
+    float_tolerance: f64,
+}
+
+impl DiffOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compares arrays as multisets: elements only need a match somewhere in the other array.
+    pub fn ignore_array_order(mut self, ignore: bool) -> Self {
+        self.ignore_array_order = ignore;
+        self
+    }
+
+    /// Skips the subtree at `path`. A `*` segment matches any key or index.
+    pub fn ignore_path(mut self, path: &str) -> ParsonResult<Self> {
+        self.ignored_paths.push(JsonPointer::parse(path)?);
+        Ok(self)
+    }
+
+    /// Considers two numbers equal when they are at most `tolerance` apart.
+    pub fn float_tolerance(mut self, tolerance: f64) -> Self {
+        self.float_tolerance = tolerance;
+        self
+    }
+
+    fn is_ignored(&self, pointer: &JsonPointer) -> bool {
+        self.ignored_paths.iter().any(|ignored| {
+            ignored.segments().len() == pointer.segments().len()
+                && ignored
+                    .segments()
+                    .iter()
+                    .zip(pointer.segments())
+                    .all(|(ignored, segment)| ignored == "*" || ignored == segment)
+        })
+    }
+}
+
+impl Value {
+    /// Lists the nodes that were added, removed or changed going from `self` to `other`.
+    pub fn diff(&self, other: &Value, options: &DiffOptions) -> Vec<Difference> {
+        let mut differences = vec![];
+        diff_values(
+            self,
+            other,
+            &mut JsonPointer::default(),
+            options,
+            &mut differences,
+        );
+        differences
+    }
+}
+
+fn diff_values(
+    old: &Value,
+    new: &Value,
+    pointer: &mut JsonPointer,
+    options: &DiffOptions,
+    differences: &mut Vec<Difference>,
+) {
+    if options.is_ignored(pointer) {
+        return;
+    }
+    match (old, new) {
+        (Value::Object(old_object), Value::Object(new_object)) => {
+            let mut keys = old_object
+                .keys()
+                .chain(
+                    new_object
+                        .keys()
+                        .filter(|key| !old_object.contains_key(*key)),
+                )
+                .collect::<Vec<_>>();
+            keys.sort();
+            for key in keys {
+                pointer.push(key.as_str());
+                match (old_object.get(key), new_object.get(key)) {
+                    (Some(old), Some(new)) => diff_values(old, new, pointer, options, differences),
+                    (Some(old), None) if !options.is_ignored(pointer) => {
+                        differences.push(Difference::Removed {
+                            pointer: pointer.clone(),
+                            value: old.clone(),
+                        })
+                    }
+                    (None, Some(new)) if !options.is_ignored(pointer) => {
+                        differences.push(Difference::Added {
+                            pointer: pointer.clone(),
+                            value: new.clone(),
+                        })
+                    }
+                    _ => {}
+                }
+                pointer.pop();
+            }
+        }
+        (Value::Array(old_array), Value::Array(new_array)) if options.ignore_array_order => {
+            diff_unordered(old_array, new_array, pointer, options, differences)
+        }
+        (Value::Array(old_array), Value::Array(new_array)) => {
+            for idx in 0..old_array.len().max(new_array.len()) {
+                pointer.push(idx.to_string());
+                match (old_array.get(idx), new_array.get(idx)) {
+                    (Some(old), Some(new)) => diff_values(old, new, pointer, options, differences),
+                    (Some(old), None) if !options.is_ignored(pointer) => {
+                        differences.push(Difference::Removed {
+                            pointer: pointer.clone(),
+                            value: old.clone(),
+                        })
+                    }
+                    (None, Some(new)) if !options.is_ignored(pointer) => {
+                        differences.push(Difference::Added {
+                            pointer: pointer.clone(),
+                            value: new.clone(),
+                        })
+                    }
+                    _ => {}
+                }
+                pointer.pop();
+            }
+        }
+        (Value::Number(Number::Float(old_number)), Value::Number(Number::Float(new_number)))
+            if (old_number - new_number).abs() <= options.float_tolerance => {}
+        _ if old == new => {}
+        _ => differences.push(Difference::Changed {
+            pointer: pointer.clone(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+    }
+}
+
+fn diff_unordered(
+    old_array: &[Value],
+    new_array: &[Value],
+    pointer: &mut JsonPointer,
+    options: &DiffOptions,
+    differences: &mut Vec<Difference>,
+) {
+    let mut matched = vec![false; new_array.len()];
+    for (old_idx, old) in old_array.iter().enumerate() {
+        pointer.push(old_idx.to_string());
+        let found = (0..new_array.len()).find(|new_idx| {
+            !matched[*new_idx] && {
+                let mut nested = vec![];
+                diff_values(old, &new_array[*new_idx], pointer, options, &mut nested);
+                nested.is_empty()
+            }
+        });
+        match found {
+            Some(new_idx) => matched[new_idx] = true,
+            None if !options.is_ignored(pointer) => differences.push(Difference::Removed {
+                pointer: pointer.clone(),
+                value: old.clone(),
+            }),
+            None => {}
+        }
+        pointer.pop();
+    }
+    for (new_idx, new) in new_array.iter().enumerate() {
+        pointer.push(new_idx.to_string());
+        if !matched[new_idx] && !options.is_ignored(pointer) {
+            differences.push(Difference::Added {
+                pointer: pointer.clone(),
+                value: new.clone(),
+            });
+        }
+        pointer.pop();
+    }
+}
+
+/// Renders differences in a unified diff like layout, meant for test output.
+///
+/// ```text
+/// --- left
+/// +++ right
+/// - /name: "old"
+/// + /name: "new"
+/// + /tags/2: "added"
+/// ```
+pub fn render_diff(differences: &[Difference]) -> String {
+    let mut rendered = String::from("--- left\n+++ right\n");
+    for difference in differences {
+        let location = match difference.pointer().to_string() {
+            pointer if pointer.is_empty() => "(root)".to_string(),
+            pointer => pointer,
+        };
+        let _ = match difference {
+            Difference::Added { value, .. } => writeln!(rendered, "+ {}: {}", location, value),
+            Difference::Removed { value, .. } => writeln!(rendered, "- {}: {}", location, value),
+            Difference::Changed { old, new, .. } => {
+                writeln!(rendered, "- {}: {}\n+ {}: {}", location, old, location, new)
+            }
+        };
+    }
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{JsonPointer, Value};
+
+    use super::{render_diff, DiffOptions, Difference};
+
+    #[test]
+    fn report_added_removed_and_changed_nodes() {
+        let old = crate::json!({"name": "a", "tags": ["x", "y"], "gone": 1});
+        let new = crate::json!({"name": "b", "tags": ["x", "y", "z"], "new": null});
+
+        let differences = old.diff(&new, &DiffOptions::new());
+
+        assert_eq!(
+            differences,
+            vec![
+                Difference::Removed {
+                    pointer: JsonPointer::parse("/gone").unwrap(),
+                    value: crate::json!(1),
+                },
+                Difference::Changed {
+                    pointer: JsonPointer::parse("/name").unwrap(),
+                    old: crate::json!("a"),
+                    new: crate::json!("b"),
+                },
+                Difference::Added {
+                    pointer: JsonPointer::parse("/new").unwrap(),
+                    value: Value::Null,
+                },
+                Difference::Added {
+                    pointer: JsonPointer::parse("/tags/2").unwrap(),
+                    value: crate::json!("z"),
+                },
+            ]
+        );
+        assert!(old.diff(&old, &DiffOptions::new()).is_empty());
+    }
+
+    #[test]
+    fn apply_options() {
+        let old = crate::json!({"items": [{"id": 1, "at": 10}, {"id": 2, "at": 11}], "score": 0.1});
+        let new =
+            crate::json!({"items": [{"id": 2, "at": 20}, {"id": 1, "at": 21}], "score": 0.1000001});
+
+        assert_eq!(old.diff(&new, &DiffOptions::new()).len(), 5);
+
+        let options = DiffOptions::new()
+            .ignore_array_order(true)
+            .ignore_path("/items/*/at")
+            .unwrap()
+            .float_tolerance(1e-3);
+        assert!(old.diff(&new, &options).is_empty());
+    }
+
+    #[test]
+    fn render_unified_style() {
+        let differences = crate::json!({"a": [1]})
+            .diff(&crate::json!({"a": [2], "b": true}), &DiffOptions::new());
+        assert_eq!(
+            render_diff(&differences),
+            "--- left\n+++ right\n- /a/0: 1\n+ /a/0: 2\n+ /b: true\n"
+        );
+        let differences = crate::json!(1).diff(&crate::json!("1"), &DiffOptions::new());
+        assert!(render_diff(&differences).contains("+ (root): \"1\""));
+    }
+}
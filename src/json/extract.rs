@@ -0,0 +1,335 @@
+use std::{borrow::Cow, str::from_utf8};
+
+use crate::{ParsingError, ParsonResult};
+
+use super::{lexer::decode_string, JsonParser, JsonPointer, Value};
+
+/// Pulls a few values out of a json document without building the whole tree.
+///
+/// The input is scanned once: subtrees that are not on one of the requested
+/// paths are skipped over without allocating anything for them, and only the
+/// matched values are parsed.
+#[derive(Debug)]
+pub struct PathExtractor {
+    paths: Vec<JsonPointer>,
+}
+
+impl PathExtractor {
+    pub fn new(paths: &[&str]) -> ParsonResult<Self> {
+        Ok(Self {
+            paths: paths
+                .iter()
+                .map(|path| JsonPointer::parse(path))
+                .collect::<ParsonResult<Vec<JsonPointer>>>()?,
+        })
+    }
+
+    /// Returns one entry per requested path, in the order they were given.
+    /// The input after the last match is not read, so it is not validated.
+    pub fn extract(&self, bytes: &[u8]) -> ParsonResult<Vec<Option<Value>>> {
+        let mut found = vec![None; self.paths.len()];
+        let live = (0..self.paths.len()).collect::<Vec<usize>>();
+        let mut scanner = Scanner {
+            bytes,
+            pos: 0,
+            remaining: self.paths.len(),
+        };
+        scanner.skip_whitespace();
+        scanner.walk(&self.paths, 0, &live, &mut found)?;
+        // the scan stops at the last match, so only a document read to its
+        // end can be checked for data after it
+        if scanner.remaining > 0 {
+            scanner.skip_whitespace();
+            if scanner.peek().is_some() {
+                return Err(scanner.error("unexpected data after the json value"));
+            }
+        }
+        Ok(found)
+    }
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    remaining: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn walk(
+        &mut self,
+        paths: &[JsonPointer],
+        depth: usize,
+        live: &[usize],
+        found: &mut [Option<Value>],
+    ) -> ParsonResult<()> {
+        if live.iter().any(|idx| paths[*idx].segments().len() == depth) {
+            return self.parse_matched(paths, depth, live, found);
+        }
+
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                if self.eat(b'}') {
+                    return Ok(());
+                }
+                loop {
+                    let key = self.scan_key()?;
+                    self.skip_whitespace();
+                    self.expect(b':')?;
+                    self.skip_whitespace();
+                    let next_live = live
+                        .iter()
+                        .copied()
+                        .filter(|idx| paths[*idx].segments()[depth] == key)
+                        .collect::<Vec<usize>>();
+                    if next_live.is_empty() {
+                        self.skip_value()?;
+                    } else {
+                        self.walk(paths, depth + 1, &next_live, found)?;
+                        if self.remaining == 0 {
+                            return Ok(());
+                        }
+                    }
+                    self.skip_whitespace();
+                    if self.eat(b'}') {
+                        return Ok(());
+                    }
+                    self.expect(b',')?;
+                    self.skip_whitespace();
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                if self.eat(b']') {
+                    return Ok(());
+                }
+                let mut index = 0;
+                loop {
+                    let next_live = live
+                        .iter()
+                        .copied()
+                        .filter(|idx| paths[*idx].segments()[depth].parse::<usize>() == Ok(index))
+                        .collect::<Vec<usize>>();
+                    if next_live.is_empty() {
+                        self.skip_value()?;
+                    } else {
+                        self.walk(paths, depth + 1, &next_live, found)?;
+                        if self.remaining == 0 {
+                            return Ok(());
+                        }
+                    }
+                    self.skip_whitespace();
+                    if self.eat(b']') {
+                        return Ok(());
+                    }
+                    self.expect(b',')?;
+                    self.skip_whitespace();
+                    index += 1;
+                }
+            }
+            _ => self.skip_value(),
+        }
+    }
+
+    fn parse_matched(
+        &mut self,
+        paths: &[JsonPointer],
+        depth: usize,
+        live: &[usize],
+        found: &mut [Option<Value>],
+    ) -> ParsonResult<()> {
+        let start = self.pos;
+        self.skip_value()?;
+        let value = JsonParser::new(&self.bytes[start..self.pos])?.parse()?;
+        for idx in live {
+            let nested = paths[*idx].segments()[depth..]
+                .iter()
+                .try_fold(&value, |value, segment| value.child(segment));
+            if let Some(nested) = nested {
+                if found[*idx].is_none() {
+                    self.remaining -= 1;
+                }
+                found[*idx] = Some(nested.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a key, decoding its escapes only when it has any.
+    fn scan_key(&mut self) -> ParsonResult<Cow<'a, str>> {
+        let start = self.pos;
+        self.skip_string()?;
+        let raw = from_utf8(&self.bytes[start..self.pos])
+            .map_err(|_| self.error("object key is not valid utf-8"))?;
+        if !raw.contains('\\') {
+            return Ok(Cow::Borrowed(&raw[1..raw.len() - 1]));
+        }
+        decode_string(raw)
+            .map(Cow::Owned)
+            .map_err(|message| self.error(&message))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> ParsonResult<()> {
+        if self.eat(byte) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", byte as char)))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\n' | b'\r' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Moves past the value starting at the current position without building it.
+    fn skip_value(&mut self) -> ParsonResult<()> {
+        match self.peek() {
+            Some(b'"') => self.skip_string(),
+            Some(b'{' | b'[') => self.skip_container(),
+            Some(b't') => self.skip_literal("true"),
+            Some(b'f') => self.skip_literal("false"),
+            Some(b'n') => self.skip_literal("null"),
+            Some(b'0'..=b'9' | b'-' | b'+') => {
+                while matches!(
+                    self.peek(),
+                    Some(b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')
+                ) {
+                    self.pos += 1;
+                }
+                Ok(())
+            }
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn skip_string(&mut self) -> ParsonResult<()> {
+        self.expect(b'"')?;
+        while let Some(byte) = self.peek() {
+            self.pos += 1;
+            match byte {
+                b'\\' => self.pos += 1,
+                b'"' => return Ok(()),
+                _ => {}
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn skip_container(&mut self) -> ParsonResult<()> {
+        let mut closers = vec![];
+        while let Some(byte) = self.peek() {
+            match byte {
+                b'"' => {
+                    self.skip_string()?;
+                    continue;
+                }
+                b'{' => closers.push(b'}'),
+                b'[' => closers.push(b']'),
+                b'}' | b']' => {
+                    if closers.pop() != Some(byte) {
+                        return Err(self.error("mismatched closing bracket"));
+                    }
+                    if closers.is_empty() {
+                        self.pos += 1;
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+            self.pos += 1;
+        }
+        Err(self.error("unterminated object or array"))
+    }
+
+    fn skip_literal(&mut self, literal: &str) -> ParsonResult<()> {
+        if self.bytes[self.pos..].starts_with(literal.as_bytes()) {
+            self.pos += literal.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {}", literal)))
+        }
+    }
+
+    fn error(&self, message: &str) -> ParsingError {
+        ParsingError {
+            message: format!("{} at byte {}", message, self.pos),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{token::Number, Value};
+
+    use super::PathExtractor;
+
+    #[test]
+    fn extract_requested_paths() {
+        let json_string = r#"{"skip": {"deep": [1, 2, {"x": "}"}]}, "name": "parson",
+            "items": [{"id": 1}, {"id": 2, "tags": ["a", "b"]}], "flag": true}"#;
+        let extractor =
+            PathExtractor::new(&["/name", "items[1].tags", "/items/1/id", "/missing"]).unwrap();
+        let found = extractor.extract(json_string.as_bytes()).unwrap();
+
+        assert_eq!(found[0], Some(Value::String("parson".to_string())));
+        assert_eq!(
+            found[1],
+            Some(Value::Array(vec![
+                Value::String("a".to_string()),
+                Value::String("b".to_string())
+            ]))
+        );
+        assert_eq!(found[2], Some(Value::Number(Number::Float(2.0))));
+        assert_eq!(found[3], None);
+    }
+
+    #[test]
+    fn extract_path_nested_in_another_match() {
+        let json_string = r#"{"a": {"b": {"c": null}}}"#;
+        let extractor = PathExtractor::new(&["/a/b/c", "/a"]).unwrap();
+        let found = extractor.extract(json_string.as_bytes()).unwrap();
+        assert_eq!(found[0], Some(Value::Null));
+        assert!(matches!(found[1], Some(Value::Object(..))));
+    }
+
+    #[test]
+    fn extract_fails_on_broken_input() {
+        let extractor = PathExtractor::new(&["/b"]).unwrap();
+        assert!(extractor.extract(br#"{"a": [1, 2"#).is_err());
+        assert!(extractor.extract(br#"{"a" 1}"#).is_err());
+        assert!(extractor.extract(br#"{"a": [1}, "b": 2}"#).is_err());
+        assert!(extractor.extract(br#"{"a": {"c": [1]]}, "b": 2}"#).is_err());
+        assert!(extractor.extract(br#"{"a": 1} garbage"#).is_err());
+
+        // nothing is read after the last match
+        assert!(extractor.extract(br#"{"b": 1} garbage"#).is_ok());
+    }
+
+    #[test]
+    fn match_keys_with_escapes() {
+        let extractor = PathExtractor::new(&["/ab", "/a\"b"]).unwrap();
+        let found = extractor
+            .extract(br#"{"a\u0062": 1, "a\"b": [true]}"#)
+            .unwrap();
+        assert_eq!(found[0], Some(Value::Number(Number::Float(1.0))));
+        assert_eq!(found[1], Some(Value::Array(vec![Value::Boolean(true)])));
+    }
+}
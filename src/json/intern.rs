@@ -0,0 +1,219 @@
+use std::{
+    collections::{HashMap, HashSet},
+    str::from_utf8,
+    sync::Arc,
+};
+
+use crate::{encoding, ParsonResult};
+
+use super::{
+    invalid_bytes,
+    token::{Number, Tokenizer},
+    tree::{self, TreeBuilder},
+    Value,
+};
+
+/// A json value whose object keys, and short strings if asked for, are shared
+/// [`Arc<str>`]s handed out by an [`Interner`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum InternedValue {
+    String(Arc<str>),
+    Number(Number),
+    Object(HashMap<Arc<str>, InternedValue>),
+    Null,
+    Boolean(bool),
+    Array(Vec<InternedValue>),
+}
+
+impl InternedValue {
+    /// Copies the value into a plain [`Value`].
+    pub fn to_value(&self) -> Value {
+        match self {
+            InternedValue::String(string) => Value::String(string.to_string()),
+            InternedValue::Number(number) => Value::Number(number.clone()),
+            InternedValue::Object(object) => Value::Object(
+                object
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_value()))
+                    .collect(),
+            ),
+            InternedValue::Null => Value::Null,
+            InternedValue::Boolean(boolean) => Value::Boolean(*boolean),
+            InternedValue::Array(array) => {
+                Value::Array(array.iter().map(InternedValue::to_value).collect())
+            }
+        }
+    }
+}
+
+/// Parses json so that equal object keys share one allocation. The pool is
+/// kept between calls, so the documents of a json lines file or of a batch of
+/// responses share their keys too.
+#[derive(Debug, Default)]
+pub struct Interner {
+    pool: HashSet<Arc<str>>,
+    strings_up_to: usize,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also interns string values of at most `len` bytes, such as enum-like
+    /// fields (`"gender": "Female"`) that repeat across records.
+    pub fn strings_up_to(mut self, len: usize) -> Self {
+        self.strings_up_to = len;
+        self
+    }
+
+    pub fn parse(&mut self, bytes: &[u8]) -> ParsonResult<InternedValue> {
+        let bytes = encoding::to_utf8(bytes)?;
+        let text = from_utf8(&bytes).map_err(|_| invalid_bytes())?;
+        let tokenizer = Tokenizer::new(&mut text.chars())?;
+        tree::build(&mut tokenizer.tokens.into_iter(), self)
+    }
+
+    /// Number of distinct strings in the pool.
+    pub fn len(&self) -> usize {
+        self.pool.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pool.is_empty()
+    }
+
+    fn intern(&mut self, string: &str) -> Arc<str> {
+        if let Some(shared) = self.pool.get(string) {
+            return shared.clone();
+        }
+        let shared: Arc<str> = Arc::from(string);
+        self.pool.insert(shared.clone());
+        shared
+    }
+}
+
+impl TreeBuilder for Interner {
+    type Key = Arc<str>;
+    type Node = InternedValue;
+
+    fn key(&mut self, key: &str) -> Arc<str> {
+        self.intern(key)
+    }
+
+    fn string(&mut self, string: String) -> InternedValue {
+        match string.len() <= self.strings_up_to {
+            true => InternedValue::String(self.intern(&string)),
+            false => InternedValue::String(Arc::from(string)),
+        }
+    }
+
+    fn number(&mut self, number: Number) -> InternedValue {
+        InternedValue::Number(number)
+    }
+
+    fn boolean(&mut self, boolean: bool) -> InternedValue {
+        InternedValue::Boolean(boolean)
+    }
+
+    fn null(&mut self) -> InternedValue {
+        InternedValue::Null
+    }
+
+    fn object(&mut self, members: Vec<(Arc<str>, InternedValue)>) -> InternedValue {
+        InternedValue::Object(members.into_iter().collect())
+    }
+
+    fn array(&mut self, elements: Vec<InternedValue>) -> InternedValue {
+        InternedValue::Array(elements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::json::JsonParser;
+
+    use super::{InternedValue, Interner};
+
+    const RECORDS: &str = r#"[
+        {"id": 1, "gender": "Female", "note": "a rather long free text note"},
+        {"id": 2, "gender": "Female", "note": "a rather long free text note"}
+    ]"#;
+
+    fn field<'a>(value: &'a InternedValue, idx: usize, key: &str) -> &'a Arc<str> {
+        let InternedValue::Array(records) = value else {
+            panic!("not an array")
+        };
+        let InternedValue::Object(record) = &records[idx] else {
+            panic!("not an object")
+        };
+        let (key, _) = record.get_key_value(key).unwrap();
+        key
+    }
+
+    fn string<'a>(value: &'a InternedValue, idx: usize, key: &str) -> &'a Arc<str> {
+        let InternedValue::Array(records) = value else {
+            panic!("not an array")
+        };
+        let InternedValue::Object(record) = &records[idx] else {
+            panic!("not an object")
+        };
+        let InternedValue::String(string) = &record[key] else {
+            panic!("not a string")
+        };
+        string
+    }
+
+    #[test]
+    fn share_repeated_keys() {
+        let mut interner = Interner::new();
+        let value = interner.parse(RECORDS.as_bytes()).unwrap();
+        assert!(Arc::ptr_eq(
+            field(&value, 0, "gender"),
+            field(&value, 1, "gender")
+        ));
+        assert!(!Arc::ptr_eq(
+            string(&value, 0, "gender"),
+            string(&value, 1, "gender")
+        ));
+        assert_eq!(interner.len(), 3);
+
+        let other = interner.parse(br#"{"id": 3}"#).unwrap();
+        let InternedValue::Object(other) = other else {
+            panic!("not an object")
+        };
+        let (key, _) = other.get_key_value("id").unwrap();
+        assert!(Arc::ptr_eq(key, field(&value, 0, "id")));
+    }
+
+    #[test]
+    fn share_short_strings_when_asked() {
+        let mut interner = Interner::new().strings_up_to(8);
+        let value = interner.parse(RECORDS.as_bytes()).unwrap();
+        assert!(Arc::ptr_eq(
+            string(&value, 0, "gender"),
+            string(&value, 1, "gender")
+        ));
+        assert!(!Arc::ptr_eq(
+            string(&value, 0, "note"),
+            string(&value, 1, "note")
+        ));
+    }
+
+    #[test]
+    fn match_the_plain_parse() {
+        for text in [
+            RECORDS,
+            r#"{"a": {"b": [1, null, true]}, "a": 2}"#,
+            "\"x\"",
+            "[1,,2]",
+        ] {
+            let plain = JsonParser::new(text.as_bytes()).unwrap().parse().unwrap();
+            let interned = Interner::new().parse(text.as_bytes()).unwrap();
+            assert_eq!(interned.to_value(), plain);
+        }
+        assert!(Interner::new().parse(b"}").is_err());
+    }
+}
@@ -0,0 +1,372 @@
+/// Where a token or value sits in the source: byte offsets plus the 1-based
+/// line and column (counted in characters) of its first character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// What a [`Lexeme`] is. Literal kinds cover the whole token: a `String`
+/// includes its quotes and escapes are left as written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    OpenCurlyBracket,
+    CloseCurlyBracket,
+    OpenSquareBracket,
+    CloseSquareBracket,
+    Comma,
+    Colon,
+    String,
+    Number,
+    /// `true` or `false`.
+    Boolean,
+    Null,
+    /// A whole run of spaces, tabs and line breaks.
+    Whitespace,
+    /// A `// line` or `/* block */` comment, as found in JSONC files.
+    Comment,
+    /// Anything that is not json: stray characters, malformed literals or
+    /// numbers, unterminated strings.
+    Invalid,
+}
+
+/// A token of json source: its kind and where it sits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lexeme {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+impl Lexeme {
+    /// The text of the token in the `source` it was lexed from.
+    pub fn text<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.span.start..self.span.end]
+    }
+}
+
+/// Splits json source into positioned tokens without decoding them.
+///
+/// Tokens are produced lazily, one per call to `next`, and cover the source
+/// without gaps: concatenating their texts gives the input back, which makes
+/// the lexer a fit for syntax highlighters and formatters.
+///
+/// It never fails: input it does not understand comes out as
+/// [`TokenKind::Invalid`] and lexing carries on after it.
+///
+/// ```
+/// use parson::{JsonLexer, JsonTokenKind};
+///
+/// let source = "{\"on\": true}";
+/// let kinds = JsonLexer::new(source)
+///     .filter(|lexeme| lexeme.kind != JsonTokenKind::Whitespace)
+///     .map(|lexeme| (lexeme.kind, lexeme.text(source)))
+///     .collect::<Vec<_>>();
+/// assert_eq!(kinds[1], (JsonTokenKind::String, "\"on\""));
+/// assert_eq!(kinds[3], (JsonTokenKind::Boolean, "true"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Lexer<'a> {
+    source: &'a str,
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            pos: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let character = self.peek()?;
+        self.pos += character.len_utf8();
+        if character == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(character)
+    }
+
+    fn bump_while(&mut self, condition: impl Fn(char) -> bool) {
+        while self.peek().is_some_and(&condition) {
+            self.bump();
+        }
+    }
+
+    fn lex_string(&mut self) -> TokenKind {
+        while let Some(character) = self.peek() {
+            match character {
+                '"' => {
+                    self.bump();
+                    return TokenKind::String;
+                }
+                '\\' => {
+                    self.bump();
+                    if self.peek() != Some('\n') {
+                        self.bump();
+                    }
+                }
+                // an unterminated string stops at the end of its line
+                '\n' => return TokenKind::Invalid,
+                _ => {
+                    self.bump();
+                }
+            }
+        }
+        TokenKind::Invalid
+    }
+}
+
+impl Lexer<'_> {
+    fn lex_comment(&mut self) -> TokenKind {
+        match self.peek() {
+            Some('/') => {
+                self.bump_while(|c| c != '\n');
+                TokenKind::Comment
+            }
+            Some('*') => {
+                self.bump();
+                while let Some(character) = self.bump() {
+                    if character == '*' && self.peek() == Some('/') {
+                        self.bump();
+                        return TokenKind::Comment;
+                    }
+                }
+                TokenKind::Invalid
+            }
+            _ => TokenKind::Invalid,
+        }
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Lexeme;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (start, line, column) = (self.pos, self.line, self.column);
+        let kind = match self.bump()? {
+            '{' => TokenKind::OpenCurlyBracket,
+            '}' => TokenKind::CloseCurlyBracket,
+            '[' => TokenKind::OpenSquareBracket,
+            ']' => TokenKind::CloseSquareBracket,
+            ',' => TokenKind::Comma,
+            ':' => TokenKind::Colon,
+            ' ' | '\t' | '\n' | '\r' => {
+                self.bump_while(|c| matches!(c, ' ' | '\t' | '\n' | '\r'));
+                TokenKind::Whitespace
+            }
+            '"' => self.lex_string(),
+            '/' => self.lex_comment(),
+            '-' | '0'..='9' => {
+                self.bump_while(|c| matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'));
+                if is_json_number(&self.source.as_bytes()[start..self.pos]) {
+                    TokenKind::Number
+                } else {
+                    TokenKind::Invalid
+                }
+            }
+            character if character.is_alphanumeric() => {
+                self.bump_while(|c| c.is_alphanumeric() || c == '_');
+                match &self.source[start..self.pos] {
+                    "true" | "false" => TokenKind::Boolean,
+                    "null" => TokenKind::Null,
+                    _ => TokenKind::Invalid,
+                }
+            }
+            _ => TokenKind::Invalid,
+        };
+        Some(Lexeme {
+            kind,
+            span: Span {
+                start,
+                end: self.pos,
+                line,
+                column,
+            },
+        })
+    }
+}
+
+pub(crate) fn is_json_number(buf: &[u8]) -> bool {
+    let mut idx = 0;
+    let digits = |idx: &mut usize| {
+        let start = *idx;
+        while buf.get(*idx).is_some_and(u8::is_ascii_digit) {
+            *idx += 1;
+        }
+        *idx > start
+    };
+    if buf.get(idx) == Some(&b'-') {
+        idx += 1;
+    }
+    if buf.get(idx) == Some(&b'0') {
+        idx += 1;
+    } else if !digits(&mut idx) {
+        return false;
+    }
+    if buf.get(idx) == Some(&b'.') {
+        idx += 1;
+        if !digits(&mut idx) {
+            return false;
+        }
+    }
+    if matches!(buf.get(idx), Some(b'e' | b'E')) {
+        idx += 1;
+        if matches!(buf.get(idx), Some(b'+' | b'-')) {
+            idx += 1;
+        }
+        if !digits(&mut idx) {
+            return false;
+        }
+    }
+    idx == buf.len()
+}
+
+/// Decodes the text of a [`TokenKind::String`] token, quotes included.
+pub(crate) fn decode_string(raw: &str) -> Result<String, String> {
+    let inner = &raw[1..raw.len() - 1];
+    let mut decoded = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(character) = chars.next() {
+        match character {
+            '\\' => {}
+            '\u{00}'..='\u{1F}' => return Err("control character in string".to_string()),
+            _ => {
+                decoded.push(character);
+                continue;
+            }
+        }
+        let unescaped = match chars.next() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{08}',
+            Some('f') => '\u{0C}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let code = read_unicode_escape(&mut chars)?;
+                let code = if (0xD800..=0xDBFF).contains(&code) {
+                    if chars.next() != Some('\\') || chars.next() != Some('u') {
+                        return Err("unpaired surrogate in unicode escape".to_string());
+                    }
+                    let low = read_unicode_escape(&mut chars)?;
+                    if !(0xDC00..=0xDFFF).contains(&low) {
+                        return Err("unpaired surrogate in unicode escape".to_string());
+                    }
+                    0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00)
+                } else {
+                    code
+                };
+                char::from_u32(code)
+                    .ok_or_else(|| "unpaired surrogate in unicode escape".to_string())?
+            }
+            Some(other) => return Err(format!("invalid escape sequence \\{}", other)),
+            None => return Err("invalid escape sequence".to_string()),
+        };
+        decoded.push(unescaped);
+    }
+    Ok(decoded)
+}
+
+fn read_unicode_escape(chars: &mut std::str::Chars) -> Result<u32, String> {
+    let hex = chars.take(4).collect::<String>();
+    u32::from_str_radix(&hex, 16)
+        .ok()
+        .filter(|_| hex.len() == 4)
+        .ok_or_else(|| format!("invalid unicode escape \\u{}", hex))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_string, Lexer, Span, TokenKind};
+
+    #[test]
+    fn lex_with_positions() {
+        let lexemes = Lexer::new("{\"a\": [1,\n  tru, -0.5e3]}").collect::<Vec<_>>();
+        let kinds = lexemes.iter().map(|l| l.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::OpenCurlyBracket,
+                TokenKind::String,
+                TokenKind::Colon,
+                TokenKind::Whitespace,
+                TokenKind::OpenSquareBracket,
+                TokenKind::Number,
+                TokenKind::Comma,
+                TokenKind::Whitespace,
+                TokenKind::Invalid,
+                TokenKind::Comma,
+                TokenKind::Whitespace,
+                TokenKind::Number,
+                TokenKind::CloseSquareBracket,
+                TokenKind::CloseCurlyBracket,
+            ]
+        );
+        assert_eq!(
+            lexemes[8].span,
+            Span {
+                start: 12,
+                end: 15,
+                line: 2,
+                column: 3
+            }
+        );
+    }
+
+    #[test]
+    fn tokens_cover_the_source() {
+        let source = "[1 ,\t\"é\" , nul] // end";
+        let text = Lexer::new(source)
+            .map(|lexeme| lexeme.text(source))
+            .collect::<String>();
+        assert_eq!(text, source);
+    }
+
+    #[test]
+    fn lex_comments() {
+        let source = "// head\n[1, /* two */ 2] /* open";
+        let kinds = Lexer::new(source)
+            .map(|lexeme| (lexeme.kind, &source[lexeme.span.start..lexeme.span.end]))
+            .filter(|(kind, _)| matches!(kind, TokenKind::Comment | TokenKind::Invalid))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                (TokenKind::Comment, "// head"),
+                (TokenKind::Comment, "/* two */"),
+                (TokenKind::Invalid, "/* open"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_string_stops_at_line_end() {
+        let lexemes = Lexer::new("\"abc\n1").collect::<Vec<_>>();
+        assert_eq!(lexemes[0].kind, TokenKind::Invalid);
+        assert_eq!(lexemes[0].span.end, 4);
+        assert_eq!(lexemes[2].kind, TokenKind::Number);
+    }
+
+    #[test]
+    fn decode_escapes() {
+        assert_eq!(decode_string(r#""a\"b\\\né😀""#).unwrap(), "a\"b\\\né😀");
+        assert!(decode_string(r#""\x""#).is_err());
+        assert!(decode_string(r#""\ud83d""#).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+/// Builds a json [`Value`](crate::JsonValue) from json-like syntax.
+///
+/// Any Rust expression that converts into a value can be interpolated, and
+/// object keys can be string literals or parenthesised expressions.
+///
+/// ```
+/// use parson::json;
+///
+/// let name = "parson";
+/// let value = json!({
+///     "name": name,
+///     "tags": ["json", "csv"],
+///     "stars": 3 * 4,
+///     "fork": null,
+///     (format!("{}_id", name)): [1, -2.5, { "nested": true }],
+/// });
+/// ```
+#[macro_export]
+macro_rules! json {
+    ($($json:tt)+) => {
+        $crate::json_internal!($($json)+)
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_internal {
+    // array elements, accumulated as expressions between the brackets
+    (@array [$($elems:expr,)*]) => {
+        ::std::vec![$($elems,)*]
+    };
+    (@array [$($elems:expr),*]) => {
+        ::std::vec![$($elems),*]
+    };
+    (@array [$($elems:expr,)*] null $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!(null)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] true $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!(true)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] false $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!(false)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!([$($array)*])] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] {$($object:tt)*} $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!({$($object)*})] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!($next),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $last:expr) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!($last)])
+    };
+    (@array [$($elems:expr),*] , $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)*] $($rest)*)
+    };
+
+    // object entries, inserted one by one into `$object`
+    (@object $object:ident ()) => {};
+    (@object $object:ident [$key:tt] ($value:expr) , $($rest:tt)*) => {
+        let _ = $object.insert(::std::convert::Into::<::std::string::String>::into($key), $value);
+        $crate::json_internal!(@object $object ($($rest)*));
+    };
+    (@object $object:ident [$key:tt] ($value:expr)) => {
+        let _ = $object.insert(::std::convert::Into::<::std::string::String>::into($key), $value);
+    };
+    (@object $object:ident [$key:tt] (: null $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$key] ($crate::json_internal!(null)) $($rest)*);
+    };
+    (@object $object:ident [$key:tt] (: true $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$key] ($crate::json_internal!(true)) $($rest)*);
+    };
+    (@object $object:ident [$key:tt] (: false $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$key] ($crate::json_internal!(false)) $($rest)*);
+    };
+    (@object $object:ident [$key:tt] (: [$($array:tt)*] $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$key] ($crate::json_internal!([$($array)*])) $($rest)*);
+    };
+    (@object $object:ident [$key:tt] (: {$($map:tt)*} $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$key] ($crate::json_internal!({$($map)*})) $($rest)*);
+    };
+    (@object $object:ident [$key:tt] (: $value:expr , $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$key] ($crate::json_internal!($value)) , $($rest)*);
+    };
+    (@object $object:ident [$key:tt] (: $value:expr)) => {
+        $crate::json_internal!(@object $object [$key] ($crate::json_internal!($value)));
+    };
+    (@object $object:ident ($key:tt : $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$key] (: $($rest)*));
+    };
+
+    (null) => {
+        $crate::JsonValue::Null
+    };
+    (true) => {
+        $crate::JsonValue::Boolean(true)
+    };
+    (false) => {
+        $crate::JsonValue::Boolean(false)
+    };
+    ([]) => {
+        $crate::JsonValue::Array(::std::vec![])
+    };
+    ([ $($tt:tt)+ ]) => {
+        $crate::JsonValue::Array($crate::json_internal!(@array [] $($tt)+))
+    };
+    ({}) => {
+        $crate::JsonValue::Object(::std::collections::HashMap::new())
+    };
+    ({ $($tt:tt)+ }) => {
+        $crate::JsonValue::Object({
+            let mut object = ::std::collections::HashMap::new();
+            $crate::json_internal!(@object object ($($tt)+));
+            object
+        })
+    };
+    ($other:expr) => {
+        $crate::JsonValue::from($other)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::json::{token::Number, Value};
+
+    #[test]
+    fn build_values_with_macro() {
+        assert_eq!(crate::json!(null), Value::Null);
+        assert_eq!(crate::json!([]), Value::Array(vec![]));
+        assert_eq!(crate::json!({}), Value::Object(HashMap::new()));
+
+        let name = "parson".to_string();
+        let value = crate::json!({
+            "name": name,
+            "list": [1, -2, true, null, [], {"a": false}],
+            (format!("{}_{}", "computed", 1)): 2 * 3,
+        });
+
+        let expected = Value::Object(HashMap::from([
+            ("name".to_string(), Value::String("parson".to_string())),
+            (
+                "list".to_string(),
+                Value::Array(vec![
+                    Value::Number(Number::Float(1.0)),
+                    Value::Number(Number::Float(-2.0)),
+                    Value::Boolean(true),
+                    Value::Null,
+                    Value::Array(vec![]),
+                    Value::Object(HashMap::from([("a".to_string(), Value::Boolean(false))])),
+                ]),
+            ),
+            ("computed_1".to_string(), Value::Number(Number::Float(6.0))),
+        ]));
+        assert_eq!(value, expected);
+    }
+}
@@ -1,132 +1,73 @@
+mod arena;
+mod canonical;
+mod cst;
+mod diff;
+mod extract;
+mod intern;
+mod lexer;
+mod macros;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod pointer;
+mod push;
+mod query;
+mod recover;
+mod spanned;
+#[cfg(feature = "async")]
+mod stream;
 mod token;
+mod tree;
 mod value;
 
-use std::{collections::HashMap, iter::Peekable, str::from_utf8};
-
-use token::{Token, Tokenizer};
+use std::str::from_utf8;
+
+pub use arena::{ArenaValue, JsonArena};
+pub use cst::JsonDocument;
+pub use diff::{render_diff, DiffOptions, Difference};
+pub use extract::PathExtractor;
+pub use intern::{InternedValue, Interner};
+pub use lexer::{Lexeme, Lexer, Span, TokenKind};
+#[cfg(feature = "rayon")]
+pub use parallel::parse_parallel;
+pub use pointer::JsonPointer;
+pub use push::{JsonEvent, JsonPushParser, JsonValueBuilder};
+pub use query::Query;
+pub use recover::{parse_recovering, parse_spanned, JsonError, RecoveredJson};
+pub use spanned::{Spanned, SpannedValue};
+#[cfg(feature = "async")]
+pub use stream::{parse_async, JsonArrayStream};
+use token::Tokenizer;
+use tree::ValueTree;
 pub use value::Value;
 
-use crate::{ParsingError, ParsonResult};
+use crate::{encoding, ParsingError, ParsonResult};
 
 pub struct JsonParser {
-    tokens: Vec<Token>,
+    tokenizer: Tokenizer,
 }
 
 impl JsonParser {
     pub fn new(bytes: &[u8]) -> ParsonResult<Self> {
-        Ok(Self {
-            tokens: Tokenizer::new(
-                &mut from_utf8(bytes)
-                    .map_err(|_| ParsingError {
-                        message: "not a valid json bytes array".to_string(),
-                    })?
-                    .chars(),
-            )?
-            .tokens,
-        })
-    }
-    pub fn parse(&self) -> ParsonResult<Value> {
-        let mut tokens = self.tokens.clone().into_iter().peekable();
-        parse_tokens(&mut tokens)
+        let bytes = encoding::to_utf8(bytes)?;
+        Self::from_text(from_utf8(&bytes).map_err(|_| invalid_bytes())?)
     }
-}
-
-fn parse_objects(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> HashMap<String, Value> {
-    let mut is_key = true;
-
-    let mut current_key: Option<String> = None;
 
-    let mut value = HashMap::<String, Value>::new();
-
-    while let Some(token) = tokens.next() {
-        match token {
-            Token::OpenCurlyBracket => {
-                if let Some(ref key) = current_key {
-                    value.insert(key.to_string(), Value::Object(parse_objects(tokens)));
-                }
-            }
-            Token::CloseCurlyBracket => break,
-            Token::OpenSquareBracket => {
-                if let Some(ref key) = current_key {
-                    value.insert(key.to_string(), Value::Array(parse_arrays(tokens)));
-                }
-            }
-            Token::StringValue(string) => {
-                if is_key {
-                    current_key = Some(string);
-                } else if let Some(ref key) = current_key {
-                    value.insert(key.to_string(), Value::String(string));
-                }
-            }
-            Token::NumberValue(number) => {
-                if let Some(ref key) = current_key {
-                    value.insert(key.to_string(), Value::Number(number));
-                }
-            }
-            Token::Boolean(boolean) => {
-                if let Some(ref key) = current_key {
-                    value.insert(key.to_string(), Value::Boolean(boolean));
-                }
-            }
-            Token::Null => {
-                if let Some(ref key) = current_key {
-                    value.insert(key.to_string(), Value::Null);
-                }
-            }
-            Token::Comma => is_key = true,
-            Token::Colon => is_key = false,
-            _ => {}
-        }
+    pub(crate) fn from_text(text: &str) -> ParsonResult<Self> {
+        Ok(Self {
+            tokenizer: Tokenizer::new(&mut text.chars())?,
+        })
     }
 
-    value
-}
-fn parse_arrays(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Vec<Value> {
-    let mut internal_value = Vec::<Value>::new();
-
-    while let Some(token) = tokens.next() {
-        match token {
-            Token::OpenCurlyBracket => {
-                internal_value.push(Value::Object(parse_objects(tokens)));
-            }
-            Token::OpenSquareBracket => internal_value.push(Value::Array(parse_arrays(tokens))),
-            Token::CloseSquareBracket => break,
-            Token::StringValue(string) => internal_value.push(Value::String(string)),
-            Token::NumberValue(number) => internal_value.push(Value::Number(number)),
-            Token::Boolean(boolean) => internal_value.push(Value::Boolean(boolean)),
-            Token::Null => internal_value.push(Value::Null),
-            _ => {}
-        }
+    pub fn parse(&self) -> ParsonResult<Value> {
+        let mut tokens = self.tokenizer.get_tokens().iter().cloned();
+        tree::build(&mut tokens, &mut ValueTree)
     }
-    internal_value
 }
 
-fn parse_tokens(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> ParsonResult<Value> {
-    let mut value = Value::Null;
-    while let Some(token) = tokens.next() {
-        match token {
-            Token::OpenCurlyBracket => {
-                // parse objects
-                value = Value::Object(parse_objects(tokens));
-            }
-            Token::OpenSquareBracket => {
-                // parse arrays
-                value = Value::Array(parse_arrays(tokens));
-            }
-            Token::StringValue(string) => value = Value::String(string),
-            Token::NumberValue(number) => value = Value::Number(number),
-            Token::Boolean(boolean) => {
-                value = Value::Boolean(boolean);
-            }
-            Token::Null => value = Value::Null,
-            _ => {
-                return Err(ParsingError {
-                    message: "Failed to parse object. Is it a valid one?".to_string(),
-                })
-            }
-        };
+pub(crate) fn invalid_bytes() -> ParsingError {
+    ParsingError {
+        message: "not a valid json bytes array".to_string(),
     }
-    Ok(value)
 }
 
 #[cfg(test)]
@@ -152,4 +93,22 @@ mod tests {
         let parser = JsonParser::new(json_string.as_bytes());
         assert!(parser.is_err());
     }
+
+    #[test]
+    fn parse_with_whitespace_around_the_value() {
+        let parser = JsonParser::new(b" [1, 2]\n").unwrap();
+        assert_eq!(parser.parse().unwrap(), crate::json!([1, 2]));
+        assert!(JsonParser::new(b"\n").unwrap().parse().is_err());
+        for text in ["\"a\" \"b\"", "1 2", "[1] [2]", "{} 1"] {
+            let error = JsonParser::new(text.as_bytes())
+                .unwrap()
+                .parse()
+                .unwrap_err();
+            assert_eq!(
+                error.message, "unexpected data after the json value",
+                "{}",
+                text
+            );
+        }
+    }
 }
@@ -0,0 +1,200 @@
+use std::{ops::Range, str::from_utf8};
+
+use rayon::prelude::*;
+
+use crate::{encoding, ParsonResult};
+
+use super::{invalid_bytes, JsonParser, Value};
+
+/// Parses a top-level array with its elements spread over the rayon thread pool.
+///
+/// Input that is not an array of well formed elements, or whose elements fail
+/// to parse, goes through the sequential parser instead, so the outcome and
+/// the error reported are the ones [`JsonParser`] gives.
+pub fn parse_parallel(bytes: &[u8]) -> ParsonResult<Value> {
+    let bytes = encoding::to_utf8(bytes)?;
+    let text = from_utf8(&bytes).map_err(|_| invalid_bytes())?;
+    let sequential = || JsonParser::from_text(text)?.parse();
+
+    let Some(elements) = split_elements(text) else {
+        return sequential();
+    };
+    match parse_elements(text, elements) {
+        Ok(values) => Ok(Value::Array(values)),
+        Err(_) => sequential(),
+    }
+}
+
+fn parse_elements(text: &str, elements: Vec<Range<usize>>) -> ParsonResult<Vec<Value>> {
+    elements
+        .into_par_iter()
+        .map(|range| JsonParser::from_text(&text[range])?.parse())
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Element {
+    Expected,
+    InScalar,
+    Done,
+}
+
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+/// Finds the byte ranges of the elements of a top-level array, without the
+/// whitespace around them, checking that each one holds exactly one value
+/// with balanced brackets. Returns `None` for anything else.
+fn split_elements(text: &str) -> Option<Vec<Range<usize>>> {
+    let bytes = text.as_bytes();
+    let mut idx = bytes.iter().position(|byte| !is_whitespace(*byte))?;
+    if bytes[idx] != b'[' {
+        return None;
+    }
+    idx += 1;
+
+    let mut elements = vec![];
+    let mut closers = vec![];
+    let mut element = Element::Expected;
+    let mut start = idx;
+    loop {
+        let byte = *bytes.get(idx)?;
+        if byte == b'"' {
+            if closers.is_empty() && element != Element::Expected {
+                return None;
+            }
+            idx = skip_string(bytes, idx)?;
+            if closers.is_empty() {
+                element = Element::Done;
+            }
+            continue;
+        }
+        if !closers.is_empty() {
+            match byte {
+                b'{' => closers.push(b'}'),
+                b'[' => closers.push(b']'),
+                b'}' | b']' if closers.pop() != Some(byte) => return None,
+                _ => {}
+            }
+            idx += 1;
+            continue;
+        }
+        match (byte, element) {
+            (b',', Element::InScalar | Element::Done) => {
+                elements.push(trim(bytes, start..idx));
+                start = idx + 1;
+                element = Element::Expected;
+            }
+            (b']', Element::InScalar | Element::Done) => {
+                elements.push(trim(bytes, start..idx));
+                break;
+            }
+            (b']', Element::Expected) if elements.is_empty() => break,
+            (_, _) if is_whitespace(byte) => {
+                if element == Element::InScalar {
+                    element = Element::Done;
+                }
+            }
+            (b'{' | b'[', Element::Expected) => {
+                closers.push(if byte == b'{' { b'}' } else { b']' });
+                element = Element::Done;
+            }
+            (b',' | b']' | b'}' | b':' | b'{' | b'[', _) => return None,
+            (_, Element::Expected | Element::InScalar) => element = Element::InScalar,
+            (_, Element::Done) => return None,
+        }
+        idx += 1;
+    }
+    bytes[idx + 1..]
+        .iter()
+        .all(|byte| is_whitespace(*byte))
+        .then_some(elements)
+}
+
+fn trim(bytes: &[u8], mut range: Range<usize>) -> Range<usize> {
+    while range.start < range.end && is_whitespace(bytes[range.start]) {
+        range.start += 1;
+    }
+    while range.start < range.end && is_whitespace(bytes[range.end - 1]) {
+        range.end -= 1;
+    }
+    range
+}
+
+/// Returns the index just past the string starting at `start`.
+fn skip_string(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut idx = start + 1;
+    loop {
+        match bytes.get(idx)? {
+            b'\\' => idx += 2,
+            b'"' => return Some(idx + 1),
+            _ => idx += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{JsonParser, Value};
+
+    use super::{parse_elements, parse_parallel, split_elements};
+
+    #[test]
+    fn split_at_top_level_commas() {
+        let text = r#" [1, "a,]", {"b": [2, 3]}, [], true ] "#;
+        let elements = split_elements(text)
+            .unwrap()
+            .into_iter()
+            .map(|range| &text[range])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            elements,
+            vec!["1", "\"a,]\"", "{\"b\": [2, 3]}", "[]", "true"]
+        );
+        assert_eq!(split_elements(" [ ] "), Some(vec![]));
+    }
+
+    #[test]
+    fn parse_elements_without_falling_back() {
+        let text = "[ 1, \"a\" ,\n{\"b\": [2, 3]} ]";
+        let elements = split_elements(text).unwrap();
+        assert_eq!(
+            Value::Array(parse_elements(text, elements).unwrap()),
+            crate::json!([1, "a", {"b": [2, 3]}])
+        );
+        let elements = split_elements("[1, 2]").unwrap();
+        assert_eq!(elements, vec![1..2, 4..5]);
+        assert!(parse_elements("[1, 2]", elements).is_ok());
+    }
+
+    #[test]
+    fn leave_irregular_input_to_the_sequential_parser() {
+        for text in [
+            "{}", "[1,,2]", "[1,]", "[1 2]", "[{]}", "[1] 2", "[\"open]", "[1",
+        ] {
+            assert!(split_elements(text).is_none(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn match_the_sequential_parse() {
+        for text in [
+            "[1, {\"a\": [true, null]}, \"x\"]",
+            "[]",
+            "{\"a\": 1}",
+            "[1, @]",
+            "[1,,2]",
+        ] {
+            let sequential = JsonParser::new(text.as_bytes()).and_then(|parser| parser.parse());
+            let parallel = parse_parallel(text.as_bytes());
+            match (sequential, parallel) {
+                (Ok(sequential), Ok(parallel)) => assert_eq!(sequential, parallel),
+                (Err(sequential), Err(parallel)) => {
+                    assert_eq!(sequential.message, parallel.message)
+                }
+                (sequential, parallel) => panic!("{:?} != {:?}", sequential, parallel),
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::fmt::Display;
+
+use crate::{ParsingError, ParsonResult};
+
+use super::Value;
+
+/// A location inside a json document.
+///
+/// It can be built from a RFC 6901 JSON Pointer (`/store/books/0/title`) or
+/// from a dotted path (`store.books[0].title`, optionally prefixed with `$`).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct JsonPointer {
+    segments: Vec<String>,
+}
+
+impl JsonPointer {
+    pub fn parse(path: &str) -> ParsonResult<Self> {
+        if path.is_empty() || path.starts_with('/') {
+            Self::parse_pointer(path)
+        } else {
+            Self::parse_dotted(path)
+        }
+    }
+
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+
+    pub fn push(&mut self, segment: impl Into<String>) {
+        self.segments.push(segment.into());
+    }
+
+    pub fn pop(&mut self) -> Option<String> {
+        self.segments.pop()
+    }
+
+    fn parse_pointer(path: &str) -> ParsonResult<Self> {
+        let segments = path
+            .split('/')
+            .skip(1)
+            .map(|segment| {
+                let mut unescaped = String::new();
+                let mut chars = segment.chars();
+                while let Some(character) = chars.next() {
+                    if character != '~' {
+                        unescaped.push(character);
+                        continue;
+                    }
+                    match chars.next() {
+                        Some('0') => unescaped.push('~'),
+                        Some('1') => unescaped.push('/'),
+                        _ => {
+                            return Err(ParsingError {
+                                message: format!("invalid escape in json pointer {}", path),
+                            })
+                        }
+                    }
+                }
+                Ok(unescaped)
+            })
+            .collect::<ParsonResult<Vec<String>>>()?;
+        Ok(Self { segments })
+    }
+
+    fn parse_dotted(path: &str) -> ParsonResult<Self> {
+        let path = path.strip_prefix('$').unwrap_or(path);
+        let mut segments = vec![];
+        let mut current = String::new();
+        let mut chars = path.chars();
+        while let Some(character) = chars.next() {
+            match character {
+                '.' => {
+                    if !current.is_empty() {
+                        segments.push(std::mem::take(&mut current));
+                    }
+                }
+                '[' => {
+                    if !current.is_empty() {
+                        segments.push(std::mem::take(&mut current));
+                    }
+                    let index = chars.by_ref().take_while(|c| *c != ']').collect::<String>();
+                    let index = index.trim_matches(|c| c == '"' || c == '\'');
+                    if index.is_empty() {
+                        return Err(ParsingError {
+                            message: format!("empty index in path {}", path),
+                        });
+                    }
+                    segments.push(index.to_string());
+                }
+                _ => current.push(character),
+            }
+        }
+        if !current.is_empty() {
+            segments.push(current);
+        }
+        Ok(Self { segments })
+    }
+}
+
+impl Display for JsonPointer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for segment in &self.segments {
+            write!(f, "/{}", segment.replace('~', "~0").replace('/', "~1"))?;
+        }
+        Ok(())
+    }
+}
+
+impl Value {
+    /// Returns the value found at `pointer`, if any.
+    pub fn pointer(&self, pointer: &JsonPointer) -> Option<&Value> {
+        pointer
+            .segments()
+            .iter()
+            .try_fold(self, |value, segment| value.child(segment))
+    }
+
+    pub(crate) fn child(&self, segment: &str) -> Option<&Value> {
+        match self {
+            Value::Object(object) => object.get(segment),
+            Value::Array(array) => segment.parse::<usize>().ok().and_then(|idx| array.get(idx)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::json::{token::Number, Value};
+
+    use super::JsonPointer;
+
+    #[test]
+    fn parse_pointer_and_dotted_path() {
+        let pointer = JsonPointer::parse("/store/books/0/ti~1tle~0").unwrap();
+        assert_eq!(pointer.segments(), ["store", "books", "0", "ti/tle~"]);
+        assert_eq!(pointer.to_string(), "/store/books/0/ti~1tle~0");
+
+        let dotted = JsonPointer::parse("$.store.books[0].title").unwrap();
+        assert_eq!(dotted.segments(), ["store", "books", "0", "title"]);
+
+        assert!(JsonPointer::parse("").unwrap().segments().is_empty());
+        assert!(JsonPointer::parse("/a~2").is_err());
+    }
+
+    #[test]
+    fn resolve_pointer() {
+        let value = Value::Object(HashMap::from([(
+            "list".to_string(),
+            Value::Array(vec![Value::Null, Value::Number(Number::Float(2.0))]),
+        )]));
+        let pointer = JsonPointer::parse("/list/1").unwrap();
+        assert_eq!(
+            value.pointer(&pointer),
+            Some(&Value::Number(Number::Float(2.0)))
+        );
+        assert!(value
+            .pointer(&JsonPointer::parse("/list/2").unwrap())
+            .is_none());
+        assert!(value
+            .pointer(&JsonPointer::parse("/missing").unwrap())
+            .is_none());
+    }
+}
@@ -0,0 +1,486 @@
+use std::collections::HashMap;
+
+use crate::{ParsingError, ParsonResult};
+
+use super::{
+    lexer::{decode_string, is_json_number},
+    token::Number,
+    Value,
+};
+
+/// Something the [`JsonPushParser`] recognised in its input.
+///
+/// Scalars are emitted as complete [`Value`]s, containers as start and end
+/// events around their content.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonEvent {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key(String),
+    Value(Value),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Container {
+    Object,
+    Array,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Expect {
+    Value { allow_close: bool },
+    Key { allow_close: bool },
+    Colon,
+    CommaOrClose,
+}
+
+#[derive(Debug)]
+struct StringState {
+    /// The string as written, from its opening quote on.
+    bytes: Vec<u8>,
+    escaped: bool,
+    is_key: bool,
+}
+
+#[derive(Debug)]
+enum Lexeme {
+    Idle,
+    String(StringState),
+    Number(Vec<u8>),
+    Literal {
+        literal: &'static [u8],
+        matched: usize,
+        value: Value,
+    },
+}
+
+/// A json parser that is fed the input piece by piece.
+///
+/// Chunks can be split anywhere, including in the middle of strings, numbers,
+/// multi-byte utf-8 sequences and `\u` escapes: the parser keeps its state
+/// between calls to [`JsonPushParser::feed`] and returns the events completed
+/// by each chunk. Several whitespace separated documents can follow each other.
+#[derive(Debug)]
+pub struct JsonPushParser {
+    lexeme: Lexeme,
+    stack: Vec<Container>,
+    expect: Expect,
+    offset: usize,
+    failed: bool,
+}
+
+impl Default for JsonPushParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonPushParser {
+    pub fn new() -> Self {
+        Self {
+            lexeme: Lexeme::Idle,
+            stack: vec![],
+            expect: Expect::Value { allow_close: false },
+            offset: 0,
+            failed: false,
+        }
+    }
+
+    /// Number of bytes consumed so far.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Depth of the container the parser is currently in, 0 at the top level.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) -> ParsonResult<Vec<JsonEvent>> {
+        if self.failed {
+            return Err(ParsingError {
+                message: "parser already failed on previous input".to_string(),
+            });
+        }
+        let mut events = vec![];
+        for byte in bytes {
+            if let Err(error) = self.process(*byte, &mut events) {
+                self.failed = true;
+                return Err(error);
+            }
+            self.offset += 1;
+        }
+        Ok(events)
+    }
+
+    /// Signals the end of the input, flushing a trailing top-level number.
+    pub fn finish(mut self) -> ParsonResult<Vec<JsonEvent>> {
+        if self.failed {
+            return Err(ParsingError {
+                message: "parser already failed on previous input".to_string(),
+            });
+        }
+        let mut events = vec![];
+        match std::mem::replace(&mut self.lexeme, Lexeme::Idle) {
+            Lexeme::Idle => {}
+            Lexeme::Number(buf) => {
+                let number = self.parse_number(&buf)?;
+                self.emit_value(number, &mut events);
+            }
+            Lexeme::String(..) | Lexeme::Literal { .. } => {
+                return Err(self.error("unexpected end of input"));
+            }
+        }
+        if !self.stack.is_empty() || self.expect == Expect::Colon {
+            return Err(self.error("unexpected end of input"));
+        }
+        Ok(events)
+    }
+
+    fn process(&mut self, byte: u8, events: &mut Vec<JsonEvent>) -> ParsonResult<()> {
+        match &mut self.lexeme {
+            Lexeme::Idle => {}
+            Lexeme::String(state) => {
+                if let Some(string) = Self::push_string_byte(state, byte, self.offset)? {
+                    let is_key = state.is_key;
+                    self.lexeme = Lexeme::Idle;
+                    if is_key {
+                        events.push(JsonEvent::Key(string));
+                        self.expect = Expect::Colon;
+                    } else {
+                        self.emit_value(Value::String(string), events);
+                    }
+                }
+                return Ok(());
+            }
+            Lexeme::Number(buf) => {
+                if matches!(byte, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E') {
+                    buf.push(byte);
+                    return Ok(());
+                }
+                let buf = std::mem::take(buf);
+                self.lexeme = Lexeme::Idle;
+                let number = self.parse_number(&buf)?;
+                self.emit_value(number, events);
+            }
+            Lexeme::Literal {
+                literal,
+                matched,
+                value,
+            } => {
+                if literal[*matched] != byte {
+                    return Err(self.error("invalid literal"));
+                }
+                *matched += 1;
+                if *matched == literal.len() {
+                    let value = value.clone();
+                    self.lexeme = Lexeme::Idle;
+                    self.emit_value(value, events);
+                }
+                return Ok(());
+            }
+        }
+
+        match byte {
+            b' ' | b'\t' | b'\n' | b'\r' => {}
+            b'{' => {
+                self.begin_value()?;
+                self.stack.push(Container::Object);
+                self.expect = Expect::Key { allow_close: true };
+                events.push(JsonEvent::StartObject);
+            }
+            b'[' => {
+                self.begin_value()?;
+                self.stack.push(Container::Array);
+                self.expect = Expect::Value { allow_close: true };
+                events.push(JsonEvent::StartArray);
+            }
+            b'}' => self.close(Container::Object, events)?,
+            b']' => self.close(Container::Array, events)?,
+            b':' => {
+                if self.expect != Expect::Colon {
+                    return Err(self.error("unexpected ':'"));
+                }
+                self.expect = Expect::Value { allow_close: false };
+            }
+            b',' => {
+                self.expect = match (self.expect, self.stack.last()) {
+                    (Expect::CommaOrClose, Some(Container::Object)) => {
+                        Expect::Key { allow_close: false }
+                    }
+                    (Expect::CommaOrClose, Some(Container::Array)) => {
+                        Expect::Value { allow_close: false }
+                    }
+                    _ => return Err(self.error("unexpected ','")),
+                };
+            }
+            b'"' => {
+                let is_key = matches!(self.expect, Expect::Key { .. });
+                if !is_key {
+                    self.begin_value()?;
+                }
+                self.lexeme = Lexeme::String(StringState {
+                    bytes: vec![b'"'],
+                    escaped: false,
+                    is_key,
+                });
+            }
+            b'-' | b'0'..=b'9' => {
+                self.begin_value()?;
+                self.lexeme = Lexeme::Number(vec![byte]);
+            }
+            b't' | b'f' | b'n' => {
+                self.begin_value()?;
+                let (literal, value): (&'static [u8], Value) = match byte {
+                    b't' => (b"true", Value::Boolean(true)),
+                    b'f' => (b"false", Value::Boolean(false)),
+                    _ => (b"null", Value::Null),
+                };
+                self.lexeme = Lexeme::Literal {
+                    literal,
+                    matched: 1,
+                    value,
+                };
+            }
+            _ => return Err(self.error(&format!("unexpected character '{}'", byte as char))),
+        }
+        Ok(())
+    }
+
+    fn begin_value(&self) -> ParsonResult<()> {
+        match self.expect {
+            Expect::Value { .. } => Ok(()),
+            _ => Err(self.error("unexpected value")),
+        }
+    }
+
+    fn close(&mut self, container: Container, events: &mut Vec<JsonEvent>) -> ParsonResult<()> {
+        let can_close = match self.expect {
+            Expect::CommaOrClose => true,
+            Expect::Key { allow_close } => allow_close && container == Container::Object,
+            Expect::Value { allow_close } => allow_close && container == Container::Array,
+            Expect::Colon => false,
+        };
+        if !can_close || self.stack.last() != Some(&container) {
+            return Err(self.error("unexpected closing bracket"));
+        }
+        self.stack.pop();
+        events.push(match container {
+            Container::Object => JsonEvent::EndObject,
+            Container::Array => JsonEvent::EndArray,
+        });
+        self.after_value();
+        Ok(())
+    }
+
+    fn emit_value(&mut self, value: Value, events: &mut Vec<JsonEvent>) {
+        events.push(JsonEvent::Value(value));
+        self.after_value();
+    }
+
+    fn after_value(&mut self) {
+        self.expect = if self.stack.is_empty() {
+            Expect::Value { allow_close: false }
+        } else {
+            Expect::CommaOrClose
+        };
+    }
+
+    /// Adds a byte of a string, returning the decoded string at its closing
+    /// quote. Escapes are decoded like the other parsers do, once the whole
+    /// string is in.
+    fn push_string_byte(
+        state: &mut StringState,
+        byte: u8,
+        offset: usize,
+    ) -> ParsonResult<Option<String>> {
+        let error = |message: &str| ParsingError {
+            message: format!("{} at byte {}", message, offset),
+        };
+        state.bytes.push(byte);
+        if state.escaped {
+            state.escaped = false;
+        } else if byte == b'\\' {
+            state.escaped = true;
+        } else if byte == b'"' {
+            let bytes = std::mem::take(&mut state.bytes);
+            let raw = String::from_utf8(bytes).map_err(|_| error("string is not valid utf-8"))?;
+            return decode_string(&raw)
+                .map(Some)
+                .map_err(|message| error(&message));
+        }
+        Ok(None)
+    }
+
+    fn parse_number(&self, buf: &[u8]) -> ParsonResult<Value> {
+        if !is_json_number(buf) {
+            return Err(self.error(&format!("invalid number {}", String::from_utf8_lossy(buf))));
+        }
+        let number = std::str::from_utf8(buf)
+            .ok()
+            .and_then(|number| number.parse::<f64>().ok())
+            .ok_or_else(|| self.error("invalid number"))?;
+        Ok(Value::Number(Number::Float(number)))
+    }
+
+    fn error(&self, message: &str) -> ParsingError {
+        ParsingError {
+            message: format!("{} at byte {}", message, self.offset),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Frame {
+    Object(HashMap<String, Value>, Option<String>),
+    Array(Vec<Value>),
+}
+
+/// Assembles the events of a [`JsonPushParser`] back into [`Value`]s.
+#[derive(Debug, Default)]
+pub struct JsonValueBuilder {
+    stack: Vec<Frame>,
+}
+
+impl JsonValueBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the value completed by `event`, if it closes a top-level value.
+    pub fn push(&mut self, event: JsonEvent) -> Option<Value> {
+        let value = match event {
+            JsonEvent::StartObject => {
+                self.stack.push(Frame::Object(HashMap::new(), None));
+                return None;
+            }
+            JsonEvent::StartArray => {
+                self.stack.push(Frame::Array(vec![]));
+                return None;
+            }
+            JsonEvent::Key(key) => {
+                if let Some(Frame::Object(_, pending)) = self.stack.last_mut() {
+                    *pending = Some(key);
+                }
+                return None;
+            }
+            JsonEvent::EndObject | JsonEvent::EndArray => match self.stack.pop()? {
+                Frame::Object(object, _) => Value::Object(object),
+                Frame::Array(array) => Value::Array(array),
+            },
+            JsonEvent::Value(value) => value,
+        };
+
+        match self.stack.last_mut() {
+            None => Some(value),
+            Some(Frame::Array(array)) => {
+                array.push(value);
+                None
+            }
+            Some(Frame::Object(object, pending)) => {
+                if let Some(key) = pending.take() {
+                    object.insert(key, value);
+                }
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::json::{token::Number, Value};
+
+    use super::{JsonEvent, JsonPushParser, JsonValueBuilder};
+
+    fn parse_in_chunks(input: &[u8], chunk_size: usize) -> Vec<Value> {
+        let mut parser = JsonPushParser::new();
+        let mut builder = JsonValueBuilder::new();
+        let mut events = vec![];
+        for chunk in input.chunks(chunk_size) {
+            events.extend(parser.feed(chunk).unwrap());
+        }
+        events.extend(parser.finish().unwrap());
+        events
+            .into_iter()
+            .filter_map(|event| builder.push(event))
+            .collect()
+    }
+
+    #[test]
+    fn same_result_for_every_chunk_size() {
+        let input = r#"{"name": "caf\u00e9 ☕ \ud83d\ude00", "list": [1, -2.5e3, true, null, {}], "nested": {"a": []}}"#;
+        let expected = Value::Object(HashMap::from([
+            ("name".to_string(), Value::String("café ☕ 😀".to_string())),
+            (
+                "list".to_string(),
+                Value::Array(vec![
+                    Value::Number(Number::Float(1.0)),
+                    Value::Number(Number::Float(-2500.0)),
+                    Value::Boolean(true),
+                    Value::Null,
+                    Value::Object(HashMap::new()),
+                ]),
+            ),
+            (
+                "nested".to_string(),
+                Value::Object(HashMap::from([("a".to_string(), Value::Array(vec![]))])),
+            ),
+        ]));
+
+        for chunk_size in 1..input.len() {
+            assert_eq!(
+                parse_in_chunks(input.as_bytes(), chunk_size),
+                vec![expected.clone()]
+            );
+        }
+    }
+
+    #[test]
+    fn emit_events_as_they_complete() {
+        let mut parser = JsonPushParser::new();
+        assert_eq!(
+            parser.feed(br#"[12, "ab"#).unwrap(),
+            vec![
+                JsonEvent::StartArray,
+                JsonEvent::Value(Value::Number(Number::Float(12.0)))
+            ]
+        );
+        assert_eq!(
+            parser.feed(br#"c"]"#).unwrap(),
+            vec![
+                JsonEvent::Value(Value::String("abc".to_string())),
+                JsonEvent::EndArray
+            ]
+        );
+        assert!(parser.finish().unwrap().is_empty());
+    }
+
+    #[test]
+    fn several_top_level_values() {
+        let values = parse_in_chunks(b"1 {\"a\": 2}\n[3]\n42", 3);
+        assert_eq!(values.len(), 4);
+        assert_eq!(values[3], Value::Number(Number::Float(42.0)));
+    }
+
+    #[test]
+    fn reject_invalid_input() {
+        for input in [
+            &b"[1,]"[..],
+            b"{\"a\" 1}",
+            b"[01]",
+            b"\"\\ud800x\"",
+            b"[tru]",
+            b"{\"a\": 1",
+            b"\"\xff\"",
+        ] {
+            let mut parser = JsonPushParser::new();
+            let result = parser.feed(input).and_then(|_| parser.finish());
+            assert!(result.is_err(), "{:?}", String::from_utf8_lossy(input));
+        }
+    }
+}
@@ -0,0 +1,1413 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use crate::{ParsingError, ParsonResult};
+
+use super::{token::Number, Value};
+
+/// A compiled jq-style expression.
+///
+/// Supported: `.`, `..`, `.key`, `."key"`, `.[expr]`, `.[]`, `.[from:to]`, `?`,
+/// pipes `|`, `,`, `//`, `and`/`or`, comparisons, `+ - * / %`,
+/// `if … then … elif … else … end`, array and object construction
+/// (`{name, "total": .a + .b, (.key): 1}`), string interpolation
+/// (`"\(.name) is \(.age)"`) and the builtins `length`, `keys`, `map(f)`,
+/// `select(f)`, `sort`, `sort_by(f)`, `group_by(f)`, `unique`, `add`, `not`,
+/// `type`, `tostring`, `tonumber`, `has(key)`, `empty`, `reverse`, `min`,
+/// `max` and `join(sep)`. Variables and user defined functions are not.
+///
+/// ```
+/// use parson::{JsonQuery, Parson};
+///
+/// let value = Parson::parse_json(r#"[{"n": "a", "v": 2}, {"n": "b", "v": 5}]"#).unwrap();
+/// let query = JsonQuery::parse(r#"map(select(.v > 3)) | .[] | "\(.n)=\(.v)""#).unwrap();
+/// assert_eq!(query.run(&value).unwrap(), vec![parson::json!("b=5")]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Query {
+    ast: Ast,
+}
+
+impl Query {
+    pub fn parse(expression: &str) -> ParsonResult<Self> {
+        Ok(Self {
+            ast: parse_expression(expression)?,
+        })
+    }
+
+    /// Runs the query against `input`, returning every value it outputs.
+    pub fn run(&self, input: &Value) -> ParsonResult<Vec<Value>> {
+        outputs(&self.ast, input)
+    }
+}
+
+impl Value {
+    /// Parses and runs a jq-style `expression`, see [`Query`].
+    pub fn query(&self, expression: &str) -> ParsonResult<Vec<Value>> {
+        Query::parse(expression)?.run(self)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Ast {
+    Identity,
+    Recurse,
+    Literal(Value),
+    Format(Vec<Part>),
+    Index(Box<Ast>, Box<Ast>),
+    Slice(Box<Ast>, Option<Box<Ast>>, Option<Box<Ast>>),
+    Iterate(Box<Ast>),
+    Try(Box<Ast>),
+    Pipe(Box<Ast>, Box<Ast>),
+    Comma(Box<Ast>, Box<Ast>),
+    Neg(Box<Ast>),
+    Binary(Op, Box<Ast>, Box<Ast>),
+    And(Box<Ast>, Box<Ast>),
+    Or(Box<Ast>, Box<Ast>),
+    Alternative(Box<Ast>, Box<Ast>),
+    If(Vec<(Ast, Ast)>, Option<Box<Ast>>),
+    Array(Option<Box<Ast>>),
+    Object(Vec<(Key, Ast)>),
+    Call(String, Vec<Ast>),
+}
+
+#[derive(Debug, Clone)]
+enum Part {
+    Text(String),
+    Expr(Ast),
+}
+
+#[derive(Debug, Clone)]
+enum Key {
+    Name(String),
+    Expr(Ast),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+const BUILTINS: [(&str, usize); 19] = [
+    ("length", 0),
+    ("keys", 0),
+    ("map", 1),
+    ("select", 1),
+    ("sort", 0),
+    ("sort_by", 1),
+    ("group_by", 1),
+    ("unique", 0),
+    ("add", 0),
+    ("not", 0),
+    ("type", 0),
+    ("tostring", 0),
+    ("tonumber", 0),
+    ("has", 1),
+    ("empty", 0),
+    ("reverse", 0),
+    ("min", 0),
+    ("max", 0),
+    ("join", 1),
+];
+
+const RESERVED: [&str; 6] = ["then", "elif", "else", "end", "and", "or"];
+
+fn error<T>(message: String) -> ParsonResult<T> {
+    Err(ParsingError { message })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Dot,
+    DotDot,
+    Field(String),
+    Ident(String),
+    Number(f64),
+    Str(Vec<Fragment>),
+    Symbol(&'static str),
+}
+
+/// A piece of a string literal: plain text or the source of an interpolation.
+#[derive(Debug, Clone, PartialEq)]
+enum Fragment {
+    Text(String),
+    Source(String),
+}
+
+const SYMBOLS: [&str; 23] = [
+    "==", "!=", "<=", ">=", "//", "|", ",", "(", ")", "[", "]", "{", "}", ":", ";", "?", "+", "-",
+    "*", "/", "%", "<", ">",
+];
+
+fn is_ident_start(character: char) -> bool {
+    character.is_alphabetic() || character == '_'
+}
+
+fn is_ident(character: char) -> bool {
+    character.is_alphanumeric() || character == '_'
+}
+
+fn tokenize(source: &str) -> ParsonResult<Vec<Tok>> {
+    let chars = source.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut idx = 0;
+    let take_while = |idx: &mut usize, condition: fn(char) -> bool| {
+        let start = *idx;
+        while chars.get(*idx).is_some_and(|c| condition(*c)) {
+            *idx += 1;
+        }
+        chars[start..*idx].iter().collect::<String>()
+    };
+    while let Some(&character) = chars.get(idx) {
+        match character {
+            _ if character.is_whitespace() => idx += 1,
+            '#' => {
+                take_while(&mut idx, |c| c != '\n');
+            }
+            '.' if chars.get(idx + 1) == Some(&'.') => {
+                tokens.push(Tok::DotDot);
+                idx += 2;
+            }
+            '.' if chars.get(idx + 1).is_some_and(|c| is_ident_start(*c)) => {
+                idx += 1;
+                tokens.push(Tok::Field(take_while(&mut idx, is_ident)));
+            }
+            '.' => {
+                tokens.push(Tok::Dot);
+                idx += 1;
+            }
+            '0'..='9' => {
+                let mut number = take_while(&mut idx, |c| c.is_ascii_digit() || c == '.');
+                if matches!(chars.get(idx), Some('e' | 'E')) {
+                    number.push('e');
+                    idx += 1;
+                    if let Some(sign @ ('+' | '-')) = chars.get(idx) {
+                        number.push(*sign);
+                        idx += 1;
+                    }
+                    number.push_str(&take_while(&mut idx, |c| c.is_ascii_digit()));
+                }
+                match number.parse() {
+                    Ok(number) => tokens.push(Tok::Number(number)),
+                    Err(_) => return error(format!("invalid number {}", number)),
+                }
+            }
+            _ if is_ident_start(character) => {
+                tokens.push(Tok::Ident(take_while(&mut idx, is_ident)))
+            }
+            '"' => {
+                let (fragments, end) = lex_string(&chars, idx + 1)?;
+                tokens.push(Tok::Str(fragments));
+                idx = end;
+            }
+            _ => {
+                let rest = chars[idx..].iter().take(2).collect::<String>();
+                match SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+                    Some(symbol) => {
+                        tokens.push(Tok::Symbol(symbol));
+                        idx += symbol.len();
+                    }
+                    None => return error(format!("unexpected character '{}'", character)),
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Lexes a string literal starting right after its opening quote, returning
+/// its fragments and the index just past the closing quote.
+fn lex_string(chars: &[char], start: usize) -> ParsonResult<(Vec<Fragment>, usize)> {
+    let mut fragments = vec![];
+    let mut text = String::new();
+    let mut idx = start;
+    loop {
+        match chars.get(idx) {
+            None => return error("unterminated string".to_string()),
+            Some('"') => {
+                if !text.is_empty() || fragments.is_empty() {
+                    fragments.push(Fragment::Text(text));
+                }
+                return Ok((fragments, idx + 1));
+            }
+            Some('\\') => {
+                let escaped = match chars.get(idx + 1) {
+                    Some('(') => {
+                        if !text.is_empty() {
+                            fragments.push(Fragment::Text(std::mem::take(&mut text)));
+                        }
+                        let end = closing_paren(chars, idx + 2)?;
+                        fragments.push(Fragment::Source(chars[idx + 2..end].iter().collect()));
+                        idx = end + 1;
+                        continue;
+                    }
+                    Some('u') => {
+                        let hex = chars.iter().skip(idx + 2).take(4).collect::<String>();
+                        idx += 4;
+                        u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .filter(|_| hex.len() == 4)
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| ParsingError {
+                                message: format!("invalid unicode escape \\u{}", hex),
+                            })?
+                    }
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some('b') => '\u{08}',
+                    Some('f') => '\u{0C}',
+                    Some(other @ ('"' | '\\' | '/')) => *other,
+                    _ => return error("invalid escape sequence in string".to_string()),
+                };
+                text.push(escaped);
+                idx += 2;
+            }
+            Some(character) => {
+                text.push(*character);
+                idx += 1;
+            }
+        }
+    }
+}
+
+fn closing_paren(chars: &[char], start: usize) -> ParsonResult<usize> {
+    let mut depth = 1;
+    let mut idx = start;
+    while let Some(character) = chars.get(idx) {
+        match character {
+            '"' => {
+                idx = lex_string(chars, idx + 1)?.1;
+                continue;
+            }
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(idx);
+                }
+            }
+            _ => {}
+        }
+        idx += 1;
+    }
+    error("unterminated string interpolation".to_string())
+}
+
+fn parse_expression(source: &str) -> ParsonResult<Ast> {
+    let mut parser = QueryParser {
+        tokens: tokenize(source)?,
+        pos: 0,
+    };
+    let ast = parser.parse_pipe()?;
+    match parser.tokens.get(parser.pos) {
+        Some(token) => error(format!("unexpected {}", describe(token))),
+        None => Ok(ast),
+    }
+}
+
+fn describe(token: &Tok) -> String {
+    match token {
+        Tok::Dot => "'.'".to_string(),
+        Tok::DotDot => "'..'".to_string(),
+        Tok::Field(name) => format!("'.{}'", name),
+        Tok::Ident(name) => format!("'{}'", name),
+        Tok::Number(number) => format!("'{}'", number),
+        Tok::Str(..) => "string".to_string(),
+        Tok::Symbol(symbol) => format!("'{}'", symbol),
+    }
+}
+
+struct QueryParser {
+    tokens: Vec<Tok>,
+    pos: usize,
+}
+
+impl QueryParser {
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(Tok::Symbol(s)) if *s == symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.peek(), Some(Tok::Ident(name)) if name == keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> ParsonResult<()> {
+        match self.eat_symbol(symbol) {
+            true => Ok(()),
+            false => self.unexpected(&format!("'{}'", symbol)),
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> ParsonResult<()> {
+        match self.eat_keyword(keyword) {
+            true => Ok(()),
+            false => self.unexpected(&format!("'{}'", keyword)),
+        }
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> ParsonResult<T> {
+        match self.peek() {
+            Some(token) => error(format!("expected {}, found {}", expected, describe(token))),
+            None => error(format!("expected {}, found end of query", expected)),
+        }
+    }
+
+    fn parse_pipe(&mut self) -> ParsonResult<Ast> {
+        let lhs = self.parse_comma()?;
+        match self.eat_symbol("|") {
+            true => Ok(Ast::Pipe(Box::new(lhs), Box::new(self.parse_pipe()?))),
+            false => Ok(lhs),
+        }
+    }
+
+    fn parse_comma(&mut self) -> ParsonResult<Ast> {
+        let mut lhs = self.parse_alternative()?;
+        while self.eat_symbol(",") {
+            lhs = Ast::Comma(Box::new(lhs), Box::new(self.parse_alternative()?));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_alternative(&mut self) -> ParsonResult<Ast> {
+        let lhs = self.parse_or()?;
+        match self.eat_symbol("//") {
+            true => Ok(Ast::Alternative(
+                Box::new(lhs),
+                Box::new(self.parse_alternative()?),
+            )),
+            false => Ok(lhs),
+        }
+    }
+
+    fn parse_or(&mut self) -> ParsonResult<Ast> {
+        let mut lhs = self.parse_and()?;
+        while self.eat_keyword("or") {
+            lhs = Ast::Or(Box::new(lhs), Box::new(self.parse_and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> ParsonResult<Ast> {
+        let mut lhs = self.parse_comparison()?;
+        while self.eat_keyword("and") {
+            lhs = Ast::And(Box::new(lhs), Box::new(self.parse_comparison()?));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_comparison(&mut self) -> ParsonResult<Ast> {
+        let lhs = self.parse_additive()?;
+        let op = match self.peek() {
+            Some(Tok::Symbol("==")) => Op::Eq,
+            Some(Tok::Symbol("!=")) => Op::Ne,
+            Some(Tok::Symbol("<")) => Op::Lt,
+            Some(Tok::Symbol("<=")) => Op::Le,
+            Some(Tok::Symbol(">")) => Op::Gt,
+            Some(Tok::Symbol(">=")) => Op::Ge,
+            _ => return Ok(lhs),
+        };
+        self.pos += 1;
+        Ok(Ast::Binary(
+            op,
+            Box::new(lhs),
+            Box::new(self.parse_additive()?),
+        ))
+    }
+
+    fn parse_additive(&mut self) -> ParsonResult<Ast> {
+        let mut lhs = self.parse_multiplicative()?;
+        loop {
+            let op = match self.peek() {
+                Some(Tok::Symbol("+")) => Op::Add,
+                Some(Tok::Symbol("-")) => Op::Sub,
+                _ => return Ok(lhs),
+            };
+            self.pos += 1;
+            lhs = Ast::Binary(op, Box::new(lhs), Box::new(self.parse_multiplicative()?));
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> ParsonResult<Ast> {
+        let mut lhs = self.parse_unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Tok::Symbol("*")) => Op::Mul,
+                Some(Tok::Symbol("/")) => Op::Div,
+                Some(Tok::Symbol("%")) => Op::Mod,
+                _ => return Ok(lhs),
+            };
+            self.pos += 1;
+            lhs = Ast::Binary(op, Box::new(lhs), Box::new(self.parse_unary()?));
+        }
+    }
+
+    fn parse_unary(&mut self) -> ParsonResult<Ast> {
+        match self.eat_symbol("-") {
+            true => Ok(Ast::Neg(Box::new(self.parse_unary()?))),
+            false => self.parse_postfix(),
+        }
+    }
+
+    fn parse_postfix(&mut self) -> ParsonResult<Ast> {
+        let mut ast = self.parse_primary()?;
+        loop {
+            ast = match self.peek().cloned() {
+                Some(Tok::Field(name)) => {
+                    self.pos += 1;
+                    Ast::Index(Box::new(ast), Box::new(Ast::Literal(Value::String(name))))
+                }
+                Some(Tok::Dot) if matches!(self.tokens.get(self.pos + 1), Some(Tok::Str(..))) => {
+                    self.pos += 1;
+                    let key = self.parse_primary()?;
+                    Ast::Index(Box::new(ast), Box::new(key))
+                }
+                Some(Tok::Symbol("[")) => {
+                    self.pos += 1;
+                    self.parse_brackets(ast)?
+                }
+                Some(Tok::Symbol("?")) => {
+                    self.pos += 1;
+                    Ast::Try(Box::new(ast))
+                }
+                _ => return Ok(ast),
+            };
+        }
+    }
+
+    /// Parses what follows the `[` of `.[]`, `.[index]` or `.[from:to]`.
+    fn parse_brackets(&mut self, target: Ast) -> ParsonResult<Ast> {
+        let target = Box::new(target);
+        if self.eat_symbol("]") {
+            return Ok(Ast::Iterate(target));
+        }
+        let from = match self.eat_symbol(":") {
+            true => None,
+            false => {
+                let index = self.parse_pipe()?;
+                if self.eat_symbol("]") {
+                    return Ok(Ast::Index(target, Box::new(index)));
+                }
+                self.expect_symbol(":")?;
+                Some(Box::new(index))
+            }
+        };
+        let to = match self.eat_symbol("]") {
+            true => None,
+            false => {
+                let to = self.parse_pipe()?;
+                self.expect_symbol("]")?;
+                Some(Box::new(to))
+            }
+        };
+        Ok(Ast::Slice(target, from, to))
+    }
+
+    fn parse_primary(&mut self) -> ParsonResult<Ast> {
+        let token = match self.peek() {
+            Some(token) => token.clone(),
+            None => return self.unexpected("an expression"),
+        };
+        self.pos += 1;
+        match token {
+            Tok::Dot => match self.peek() {
+                Some(Tok::Str(..)) => {
+                    let key = self.parse_primary()?;
+                    Ok(Ast::Index(Box::new(Ast::Identity), Box::new(key)))
+                }
+                _ => Ok(Ast::Identity),
+            },
+            Tok::DotDot => Ok(Ast::Recurse),
+            Tok::Field(name) => Ok(Ast::Index(
+                Box::new(Ast::Identity),
+                Box::new(Ast::Literal(Value::String(name))),
+            )),
+            Tok::Number(number) => Ok(Ast::Literal(Value::Number(Number::Float(number)))),
+            Tok::Str(fragments) => string_ast(fragments),
+            Tok::Symbol("(") => {
+                let inner = self.parse_pipe()?;
+                self.expect_symbol(")")?;
+                Ok(inner)
+            }
+            Tok::Symbol("[") => {
+                if self.eat_symbol("]") {
+                    return Ok(Ast::Array(None));
+                }
+                let inner = self.parse_pipe()?;
+                self.expect_symbol("]")?;
+                Ok(Ast::Array(Some(Box::new(inner))))
+            }
+            Tok::Symbol("{") => self.parse_object(),
+            Tok::Ident(name) => match name.as_str() {
+                "true" => Ok(Ast::Literal(Value::Boolean(true))),
+                "false" => Ok(Ast::Literal(Value::Boolean(false))),
+                "null" => Ok(Ast::Literal(Value::Null)),
+                "if" => self.parse_if(),
+                _ if RESERVED.contains(&name.as_str()) => {
+                    self.pos -= 1;
+                    self.unexpected("an expression")
+                }
+                _ => self.parse_call(name),
+            },
+            Tok::Symbol(..) => {
+                self.pos -= 1;
+                self.unexpected("an expression")
+            }
+        }
+    }
+
+    fn parse_call(&mut self, name: String) -> ParsonResult<Ast> {
+        let mut args = vec![];
+        if self.eat_symbol("(") {
+            args.push(self.parse_pipe()?);
+            while self.eat_symbol(";") {
+                args.push(self.parse_pipe()?);
+            }
+            self.expect_symbol(")")?;
+        }
+        if !BUILTINS.contains(&(name.as_str(), args.len())) {
+            return error(format!("unknown function {}/{}", name, args.len()));
+        }
+        Ok(Ast::Call(name, args))
+    }
+
+    fn parse_if(&mut self) -> ParsonResult<Ast> {
+        let mut branches = vec![];
+        loop {
+            let condition = self.parse_pipe()?;
+            self.expect_keyword("then")?;
+            branches.push((condition, self.parse_pipe()?));
+            if !self.eat_keyword("elif") {
+                break;
+            }
+        }
+        let otherwise = match self.eat_keyword("else") {
+            true => Some(Box::new(self.parse_pipe()?)),
+            false => None,
+        };
+        self.expect_keyword("end")?;
+        Ok(Ast::If(branches, otherwise))
+    }
+
+    /// Object values stop at commas but may still be pipelines.
+    fn parse_object_value(&mut self) -> ParsonResult<Ast> {
+        let lhs = self.parse_alternative()?;
+        match self.eat_symbol("|") {
+            true => Ok(Ast::Pipe(
+                Box::new(lhs),
+                Box::new(self.parse_object_value()?),
+            )),
+            false => Ok(lhs),
+        }
+    }
+
+    fn parse_object(&mut self) -> ParsonResult<Ast> {
+        let mut entries = vec![];
+        while !self.eat_symbol("}") {
+            let key = match self.peek().cloned() {
+                Some(Tok::Ident(name)) => {
+                    self.pos += 1;
+                    Key::Name(name)
+                }
+                Some(Tok::Str(fragments)) => {
+                    self.pos += 1;
+                    match string_ast(fragments)? {
+                        Ast::Literal(Value::String(name)) => Key::Name(name),
+                        format => Key::Expr(format),
+                    }
+                }
+                Some(Tok::Symbol("(")) => {
+                    self.pos += 1;
+                    let key = self.parse_pipe()?;
+                    self.expect_symbol(")")?;
+                    Key::Expr(key)
+                }
+                _ => return self.unexpected("an object key"),
+            };
+            let value = match (self.eat_symbol(":"), &key) {
+                (true, _) => self.parse_object_value()?,
+                (false, Key::Name(name)) => Ast::Index(
+                    Box::new(Ast::Identity),
+                    Box::new(Ast::Literal(Value::String(name.clone()))),
+                ),
+                (false, Key::Expr(..)) => return self.unexpected("':'"),
+            };
+            entries.push((key, value));
+            if !self.eat_symbol(",") {
+                self.expect_symbol("}")?;
+                break;
+            }
+        }
+        Ok(Ast::Object(entries))
+    }
+}
+
+fn string_ast(fragments: Vec<Fragment>) -> ParsonResult<Ast> {
+    if let [Fragment::Text(text)] = fragments.as_slice() {
+        return Ok(Ast::Literal(Value::String(text.clone())));
+    }
+    fragments
+        .into_iter()
+        .map(|fragment| match fragment {
+            Fragment::Text(text) => Ok(Part::Text(text)),
+            Fragment::Source(source) => parse_expression(&source).map(Part::Expr),
+        })
+        .collect::<ParsonResult<Vec<_>>>()
+        .map(Ast::Format)
+}
+
+fn number(number: f64) -> Value {
+    Value::Number(Number::Float(number))
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Boolean(..) => "boolean",
+        Value::Number(..) => "number",
+        Value::String(..) => "string",
+        Value::Array(..) => "array",
+        Value::Object(..) => "object",
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Boolean(false))
+}
+
+/// The text of a value when interpolated: strings as they are, the rest as json.
+fn plain(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn outputs(ast: &Ast, input: &Value) -> ParsonResult<Vec<Value>> {
+    let mut out = vec![];
+    eval(ast, input, &mut out)?;
+    Ok(out)
+}
+
+fn eval(ast: &Ast, input: &Value, out: &mut Vec<Value>) -> ParsonResult<()> {
+    match ast {
+        Ast::Identity => out.push(input.clone()),
+        Ast::Recurse => recurse(input, out),
+        Ast::Literal(value) => out.push(value.clone()),
+        Ast::Format(parts) => {
+            let mut strings = vec![String::new()];
+            for part in parts {
+                match part {
+                    Part::Text(text) => strings.iter_mut().for_each(|s| s.push_str(text)),
+                    Part::Expr(expr) => {
+                        let values = outputs(expr, input)?;
+                        strings = strings
+                            .iter()
+                            .flat_map(|s| values.iter().map(move |v| format!("{}{}", s, plain(v))))
+                            .collect();
+                    }
+                }
+            }
+            out.extend(strings.into_iter().map(Value::String));
+        }
+        Ast::Index(target, key) => {
+            for key in outputs(key, input)? {
+                for target in outputs(target, input)? {
+                    out.push(index(&target, &key)?);
+                }
+            }
+        }
+        Ast::Slice(target, from, to) => {
+            let bound = |bound: &Option<Box<Ast>>| match bound {
+                Some(bound) => outputs(bound, input),
+                None => Ok(vec![Value::Null]),
+            };
+            for to in bound(to)? {
+                for from in bound(from)? {
+                    for target in outputs(target, input)? {
+                        out.push(slice(&target, &from, &to)?);
+                    }
+                }
+            }
+        }
+        Ast::Iterate(target) => {
+            for target in outputs(target, input)? {
+                out.extend(iterate(&target)?);
+            }
+        }
+        Ast::Try(inner) => {
+            let _ = eval(inner, input, out);
+        }
+        Ast::Pipe(lhs, rhs) => {
+            for value in outputs(lhs, input)? {
+                eval(rhs, &value, out)?;
+            }
+        }
+        Ast::Comma(lhs, rhs) => {
+            eval(lhs, input, out)?;
+            eval(rhs, input, out)?;
+        }
+        Ast::Neg(inner) => {
+            for value in outputs(inner, input)? {
+                match value {
+                    Value::Number(Number::Float(value)) => out.push(number(-value)),
+                    value => return error(format!("cannot negate {}", type_name(&value))),
+                }
+            }
+        }
+        Ast::Binary(op, lhs, rhs) => {
+            for rhs in outputs(rhs, input)? {
+                for lhs in outputs(lhs, input)? {
+                    out.push(binary(*op, &lhs, &rhs)?);
+                }
+            }
+        }
+        Ast::And(lhs, rhs) | Ast::Or(lhs, rhs) => {
+            let is_and = matches!(ast, Ast::And(..));
+            for lhs in outputs(lhs, input)? {
+                if truthy(&lhs) != is_and {
+                    out.push(Value::Boolean(!is_and));
+                    continue;
+                }
+                for rhs in outputs(rhs, input)? {
+                    out.push(Value::Boolean(truthy(&rhs)));
+                }
+            }
+        }
+        Ast::Alternative(lhs, rhs) => {
+            let mut found = vec![];
+            let _ = eval(lhs, input, &mut found);
+            found.retain(truthy);
+            match found.is_empty() {
+                true => eval(rhs, input, out)?,
+                false => out.extend(found),
+            }
+        }
+        Ast::If(branches, otherwise) => eval_if(branches, otherwise.as_deref(), input, out)?,
+        Ast::Array(inner) => out.push(Value::Array(match inner {
+            Some(inner) => outputs(inner, input)?,
+            None => vec![],
+        })),
+        Ast::Object(entries) => {
+            let mut objects = vec![HashMap::new()];
+            for (key, value) in entries {
+                let keys = match key {
+                    Key::Name(name) => vec![name.clone()],
+                    Key::Expr(expr) => outputs(expr, input)?
+                        .into_iter()
+                        .map(|key| match key {
+                            Value::String(key) => Ok(key),
+                            key => error(format!(
+                                "object keys must be strings, got {}",
+                                type_name(&key)
+                            )),
+                        })
+                        .collect::<ParsonResult<_>>()?,
+                };
+                let values = outputs(value, input)?;
+                let mut extended = vec![];
+                for object in &objects {
+                    for key in &keys {
+                        for value in &values {
+                            let mut object = object.clone();
+                            object.insert(key.clone(), value.clone());
+                            extended.push(object);
+                        }
+                    }
+                }
+                objects = extended;
+            }
+            out.extend(objects.into_iter().map(Value::Object));
+        }
+        Ast::Call(name, args) => call(name, args, input, out)?,
+    }
+    Ok(())
+}
+
+fn eval_if(
+    branches: &[(Ast, Ast)],
+    otherwise: Option<&Ast>,
+    input: &Value,
+    out: &mut Vec<Value>,
+) -> ParsonResult<()> {
+    let Some(((condition, body), rest)) = branches.split_first() else {
+        return match otherwise {
+            Some(otherwise) => eval(otherwise, input, out),
+            None => {
+                out.push(input.clone());
+                Ok(())
+            }
+        };
+    };
+    for condition in outputs(condition, input)? {
+        match truthy(&condition) {
+            true => eval(body, input, out)?,
+            false => eval_if(rest, otherwise, input, out)?,
+        }
+    }
+    Ok(())
+}
+
+fn recurse(value: &Value, out: &mut Vec<Value>) {
+    out.push(value.clone());
+    if let Ok(children) = iterate(value) {
+        for child in children {
+            recurse(&child, out);
+        }
+    }
+}
+
+fn index(target: &Value, key: &Value) -> ParsonResult<Value> {
+    match (target, key) {
+        (Value::Object(object), Value::String(key)) => {
+            Ok(object.get(key).cloned().unwrap_or(Value::Null))
+        }
+        (Value::Array(array), Value::Number(Number::Float(idx))) => {
+            let idx = match *idx < 0.0 {
+                true => array.len() as f64 + idx.floor(),
+                false => idx.floor(),
+            };
+            Ok(match idx >= 0.0 {
+                true => array.get(idx as usize).cloned().unwrap_or(Value::Null),
+                false => Value::Null,
+            })
+        }
+        (Value::Null, Value::String(..) | Value::Number(..)) => Ok(Value::Null),
+        (target, key) => error(format!(
+            "cannot index {} with {}",
+            type_name(target),
+            match key {
+                Value::String(key) => format!("\"{}\"", key),
+                key => type_name(key).to_string(),
+            }
+        )),
+    }
+}
+
+fn slice(target: &Value, from: &Value, to: &Value) -> ParsonResult<Value> {
+    let bounds = |len: usize| -> ParsonResult<(usize, usize)> {
+        let bound = |value: &Value, default: usize| match value {
+            Value::Null => Ok(default),
+            Value::Number(Number::Float(idx)) => {
+                let idx = match *idx < 0.0 {
+                    true => len as f64 + idx.floor(),
+                    false => idx.floor(),
+                };
+                Ok(idx.clamp(0.0, len as f64) as usize)
+            }
+            value => error(format!(
+                "slice bounds must be numbers, got {}",
+                type_name(value)
+            )),
+        };
+        let from = bound(from, 0)?;
+        Ok((from, bound(to, len)?.max(from)))
+    };
+    match target {
+        Value::Null => Ok(Value::Null),
+        Value::Array(array) => {
+            let (from, to) = bounds(array.len())?;
+            Ok(Value::Array(array[from..to].to_vec()))
+        }
+        Value::String(string) => {
+            let chars = string.chars().collect::<Vec<_>>();
+            let (from, to) = bounds(chars.len())?;
+            Ok(Value::String(chars[from..to].iter().collect()))
+        }
+        target => error(format!("cannot slice {}", type_name(target))),
+    }
+}
+
+fn iterate(value: &Value) -> ParsonResult<Vec<Value>> {
+    match value {
+        Value::Array(array) => Ok(array.clone()),
+        Value::Object(object) => {
+            let mut entries = object.iter().collect::<Vec<_>>();
+            entries.sort_by_key(|(key, _)| *key);
+            Ok(entries
+                .into_iter()
+                .map(|(_, value)| value.clone())
+                .collect())
+        }
+        value => error(format!("cannot iterate over {}", type_name(value))),
+    }
+}
+
+/// Orders values the way jq does: null, false, true, numbers, strings,
+/// arrays, then objects.
+fn compare(lhs: &Value, rhs: &Value) -> Ordering {
+    let rank = |value: &Value| match value {
+        Value::Null => 0,
+        Value::Boolean(false) => 1,
+        Value::Boolean(true) => 2,
+        Value::Number(..) => 3,
+        Value::String(..) => 4,
+        Value::Array(..) => 5,
+        Value::Object(..) => 6,
+    };
+    match (lhs, rhs) {
+        (Value::Number(Number::Float(lhs)), Value::Number(Number::Float(rhs))) => {
+            lhs.partial_cmp(rhs).unwrap_or(Ordering::Equal)
+        }
+        (Value::String(lhs), Value::String(rhs)) => lhs.cmp(rhs),
+        (Value::Array(lhs), Value::Array(rhs)) => compare_arrays(lhs, rhs),
+        (Value::Object(lhs), Value::Object(rhs)) => {
+            let sorted_keys = |object: &HashMap<String, Value>| {
+                let mut keys = object.keys().cloned().collect::<Vec<_>>();
+                keys.sort();
+                keys
+            };
+            let (lhs_keys, rhs_keys) = (sorted_keys(lhs), sorted_keys(rhs));
+            lhs_keys.cmp(&rhs_keys).then_with(|| {
+                lhs_keys
+                    .iter()
+                    .map(|key| compare(&lhs[key], &rhs[key]))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        _ => rank(lhs).cmp(&rank(rhs)),
+    }
+}
+
+fn compare_arrays(lhs: &[Value], rhs: &[Value]) -> Ordering {
+    lhs.iter()
+        .zip(rhs)
+        .map(|(lhs, rhs)| compare(lhs, rhs))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or_else(|| lhs.len().cmp(&rhs.len()))
+}
+
+fn binary(op: Op, lhs: &Value, rhs: &Value) -> ParsonResult<Value> {
+    use Value::{Array, Null, Object, String};
+    let numbers = match (lhs, rhs) {
+        (Value::Number(Number::Float(lhs)), Value::Number(Number::Float(rhs))) => {
+            Some((*lhs, *rhs))
+        }
+        _ => None,
+    };
+    let value = match (op, numbers) {
+        (Op::Eq, _) => Value::Boolean(compare(lhs, rhs).is_eq()),
+        (Op::Ne, _) => Value::Boolean(compare(lhs, rhs).is_ne()),
+        (Op::Lt, _) => Value::Boolean(compare(lhs, rhs).is_lt()),
+        (Op::Le, _) => Value::Boolean(compare(lhs, rhs).is_le()),
+        (Op::Gt, _) => Value::Boolean(compare(lhs, rhs).is_gt()),
+        (Op::Ge, _) => Value::Boolean(compare(lhs, rhs).is_ge()),
+        (Op::Add, Some((lhs, rhs))) => number(lhs + rhs),
+        (Op::Sub, Some((lhs, rhs))) => number(lhs - rhs),
+        (Op::Mul, Some((lhs, rhs))) => number(lhs * rhs),
+        (Op::Div | Op::Mod, Some((_, 0.0))) => return error("cannot divide by zero".to_string()),
+        (Op::Div, Some((lhs, rhs))) => number(lhs / rhs),
+        (Op::Mod, Some((lhs, rhs))) => {
+            // like jq, the remainder of the integer parts
+            let (lhs, rhs) = (lhs.trunc() as i64, rhs.trunc() as i64);
+            if rhs == 0 {
+                return error("cannot divide by zero".to_string());
+            }
+            number(lhs.wrapping_rem(rhs) as f64)
+        }
+        (Op::Add, None) => match (lhs, rhs) {
+            (Null, value) | (value, Null) => value.clone(),
+            (String(lhs), String(rhs)) => String(format!("{}{}", lhs, rhs)),
+            (Array(lhs), Array(rhs)) => Array(lhs.iter().chain(rhs).cloned().collect()),
+            (Object(lhs), Object(rhs)) => Object(
+                lhs.iter()
+                    .chain(rhs)
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+            ),
+            _ => {
+                return error(format!(
+                    "cannot add {} and {}",
+                    type_name(lhs),
+                    type_name(rhs)
+                ))
+            }
+        },
+        (Op::Sub, None) => match (lhs, rhs) {
+            (Array(lhs), Array(rhs)) => Array(
+                lhs.iter()
+                    .filter(|item| !rhs.contains(item))
+                    .cloned()
+                    .collect(),
+            ),
+            _ => {
+                return error(format!(
+                    "cannot subtract {} from {}",
+                    type_name(rhs),
+                    type_name(lhs)
+                ))
+            }
+        },
+        (Op::Div, None) => match (lhs, rhs) {
+            (String(lhs), String(rhs)) => Array(
+                lhs.split(rhs.as_str())
+                    .map(|part| String(part.to_string()))
+                    .collect(),
+            ),
+            _ => {
+                return error(format!(
+                    "cannot divide {} by {}",
+                    type_name(lhs),
+                    type_name(rhs)
+                ))
+            }
+        },
+        (Op::Mul | Op::Mod, None) => {
+            return error(format!(
+                "cannot {} {} and {}",
+                if op == Op::Mul {
+                    "multiply"
+                } else {
+                    "take the remainder of"
+                },
+                type_name(lhs),
+                type_name(rhs)
+            ))
+        }
+    };
+    Ok(value)
+}
+
+fn array_input<'a>(name: &str, input: &'a Value) -> ParsonResult<&'a Vec<Value>> {
+    match input {
+        Value::Array(array) => Ok(array),
+        value => error(format!("{} needs an array, got {}", name, type_name(value))),
+    }
+}
+
+/// Pairs every element with the outputs of `f` on it, sorted by the latter.
+fn sorted_by(name: &str, f: &Ast, input: &Value) -> ParsonResult<Vec<(Value, Value)>> {
+    let mut keyed = array_input(name, input)?
+        .iter()
+        .map(|item| Ok((Value::Array(outputs(f, item)?), item.clone())))
+        .collect::<ParsonResult<Vec<_>>>()?;
+    keyed.sort_by(|(a, _), (b, _)| compare(a, b));
+    Ok(keyed)
+}
+
+fn call(name: &str, args: &[Ast], input: &Value, out: &mut Vec<Value>) -> ParsonResult<()> {
+    let value = match (name, args) {
+        ("empty", []) => return Ok(()),
+        ("length", []) => match input {
+            Value::Null => number(0.0),
+            Value::Number(Number::Float(value)) => number(value.abs()),
+            Value::String(string) => number(string.chars().count() as f64),
+            Value::Array(array) => number(array.len() as f64),
+            Value::Object(object) => number(object.len() as f64),
+            Value::Boolean(..) => return error("boolean has no length".to_string()),
+        },
+        ("keys", []) => match input {
+            Value::Object(object) => {
+                let mut keys = object.keys().cloned().collect::<Vec<_>>();
+                keys.sort();
+                Value::Array(keys.into_iter().map(Value::String).collect())
+            }
+            Value::Array(array) => {
+                Value::Array((0..array.len()).map(|idx| number(idx as f64)).collect())
+            }
+            value => return error(format!("{} has no keys", type_name(value))),
+        },
+        ("map", [f]) => {
+            let mut mapped = vec![];
+            for item in iterate(input)? {
+                eval(f, &item, &mut mapped)?;
+            }
+            Value::Array(mapped)
+        }
+        ("select", [f]) => {
+            for condition in outputs(f, input)? {
+                if truthy(&condition) {
+                    out.push(input.clone());
+                }
+            }
+            return Ok(());
+        }
+        ("sort", []) => {
+            let mut sorted = array_input(name, input)?.clone();
+            sorted.sort_by(compare);
+            Value::Array(sorted)
+        }
+        ("sort_by", [f]) => Value::Array(
+            sorted_by(name, f, input)?
+                .into_iter()
+                .map(|(_, item)| item)
+                .collect(),
+        ),
+        ("group_by", [f]) => {
+            let mut groups: Vec<(Value, Vec<Value>)> = vec![];
+            for (key, item) in sorted_by(name, f, input)? {
+                match groups.last_mut() {
+                    Some((last, group)) if compare(last, &key).is_eq() => group.push(item),
+                    _ => groups.push((key, vec![item])),
+                }
+            }
+            Value::Array(
+                groups
+                    .into_iter()
+                    .map(|(_, group)| Value::Array(group))
+                    .collect(),
+            )
+        }
+        ("unique", []) => {
+            let mut unique = array_input(name, input)?.clone();
+            unique.sort_by(compare);
+            unique.dedup_by(|a, b| compare(a, b).is_eq());
+            Value::Array(unique)
+        }
+        ("add", []) => iterate(input)?
+            .iter()
+            .try_fold(Value::Null, |sum, item| binary(Op::Add, &sum, item))?,
+        ("not", []) => Value::Boolean(!truthy(input)),
+        ("type", []) => Value::String(type_name(input).to_string()),
+        ("tostring", []) => Value::String(plain(input)),
+        ("tonumber", []) => match input {
+            Value::Number(..) => input.clone(),
+            Value::String(string) => match string.trim().parse::<f64>() {
+                Ok(parsed) => number(parsed),
+                Err(_) => return error(format!("cannot parse \"{}\" as a number", string)),
+            },
+            value => return error(format!("cannot parse {} as a number", type_name(value))),
+        },
+        ("has", [key]) => {
+            for key in outputs(key, input)? {
+                out.push(Value::Boolean(match (input, &key) {
+                    (Value::Object(object), Value::String(key)) => object.contains_key(key),
+                    (Value::Array(array), Value::Number(Number::Float(idx))) => {
+                        *idx >= 0.0 && (*idx as usize) < array.len()
+                    }
+                    _ => {
+                        return error(format!(
+                            "cannot check whether {} has a {} key",
+                            type_name(input),
+                            type_name(&key)
+                        ))
+                    }
+                }));
+            }
+            return Ok(());
+        }
+        ("reverse", []) => match input {
+            Value::Null => Value::Array(vec![]),
+            Value::String(string) => Value::String(string.chars().rev().collect()),
+            value => Value::Array(array_input(name, value)?.iter().rev().cloned().collect()),
+        },
+        ("min", []) => array_input(name, input)?
+            .iter()
+            .min_by(|a, b| compare(a, b))
+            .cloned()
+            .unwrap_or(Value::Null),
+        ("max", []) => array_input(name, input)?
+            .iter()
+            .max_by(|a, b| compare(a, b))
+            .cloned()
+            .unwrap_or(Value::Null),
+        ("join", [separator]) => {
+            let items = array_input(name, input)?;
+            for separator in outputs(separator, input)? {
+                let Value::String(separator) = separator else {
+                    return error("join needs a string separator".to_string());
+                };
+                let joined = items
+                    .iter()
+                    .map(|item| match item {
+                        Value::Null => String::new(),
+                        item => plain(item),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(&separator);
+                out.push(Value::String(joined));
+            }
+            return Ok(());
+        }
+        _ => return error(format!("unknown function {}/{}", name, args.len())),
+    };
+    out.push(value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::Value;
+
+    use super::Query;
+
+    fn run(expression: &str, input: Value) -> Vec<Value> {
+        Query::parse(expression).unwrap().run(&input).unwrap()
+    }
+
+    #[test]
+    fn access_paths() {
+        let input = crate::json!({"a": {"b": [10, 20, 30]}, "key with space": 1});
+        assert_eq!(run(".a.b[1]", input.clone()), vec![crate::json!(20)]);
+        assert_eq!(run(".a.b[-1]", input.clone()), vec![crate::json!(30)]);
+        assert_eq!(run(".a.b[1:]", input.clone()), vec![crate::json!([20, 30])]);
+        assert_eq!(
+            run(".\"key with space\"", input.clone()),
+            vec![crate::json!(1)]
+        );
+        assert_eq!(run(".a.b[]", input.clone()).len(), 3);
+        assert_eq!(run(".missing.deeper", input.clone()), vec![Value::Null]);
+        assert_eq!(run(".a.b.c?", input.clone()), vec![]);
+        assert!(Query::parse(".a.b.c").unwrap().run(&input).is_err());
+        assert_eq!(
+            run("[..] | length", crate::json!([[1]])),
+            vec![crate::json!(3)]
+        );
+    }
+
+    #[test]
+    fn pipes_map_and_select() {
+        let input = crate::json!([{"n": "a", "v": 1}, {"n": "b", "v": 4}, {"n": "c", "v": 3}]);
+        assert_eq!(
+            run("map(select(.v >= 3) | .n)", input.clone()),
+            vec![crate::json!(["b", "c"])]
+        );
+        assert_eq!(
+            run(".[] | select(.n == \"a\" or .v > 3) | .v", input.clone()),
+            vec![crate::json!(1), crate::json!(4)]
+        );
+        assert_eq!(
+            run(".[0].v, .[1].v", input),
+            vec![crate::json!(1), crate::json!(4)]
+        );
+    }
+
+    #[test]
+    fn construct_values() {
+        let input = crate::json!({"name": "ann", "a": 2, "b": 3, "k": "dyn"});
+        assert_eq!(
+            run(
+                "{name, total: .a + .b, (.k): [.a, .b] | length, \"x y\": null}",
+                input
+            ),
+            vec![crate::json!({"name": "ann", "total": 5, "dyn": 2, "x y": null})]
+        );
+        assert_eq!(
+            run("{a: (1, 2)}", Value::Null),
+            vec![crate::json!({"a": 1}), crate::json!({"a": 2})]
+        );
+    }
+
+    #[test]
+    fn arithmetic_and_comparisons() {
+        assert_eq!(
+            run("1 + 2 * 3 - -4 / 2", Value::Null),
+            vec![crate::json!(9)]
+        );
+        assert_eq!(run("7 % 3", Value::Null), vec![crate::json!(1)]);
+        assert_eq!(run("-7.5 % 2.9", Value::Null), vec![crate::json!(-1)]);
+        assert!(Query::parse("7 % 0.5").unwrap().run(&Value::Null).is_err());
+        assert_eq!(
+            run("-9223372036854775808 % -1", Value::Null),
+            vec![crate::json!(0)]
+        );
+        assert_eq!(run("\"a\" + \"b\"", Value::Null), vec![crate::json!("ab")]);
+        assert_eq!(
+            run("[1, 2, 3] - [2]", Value::Null),
+            vec![crate::json!([1, 3])]
+        );
+        assert_eq!(
+            run("null < false and 1 < \"a\"", Value::Null),
+            vec![crate::json!(true)]
+        );
+        assert_eq!(
+            run(".x // \"default\"", crate::json!({})),
+            vec![crate::json!("default")]
+        );
+        assert_eq!(
+            run(
+                "if . > 2 then \"big\" elif . > 0 then \"small\" else \"none\" end",
+                crate::json!(1)
+            ),
+            vec![crate::json!("small")]
+        );
+        assert!(Query::parse("1 / 0").unwrap().run(&Value::Null).is_err());
+    }
+
+    #[test]
+    fn builtins() {
+        let input = crate::json!([{"g": "x", "v": 3}, {"g": "y", "v": 1}, {"g": "x", "v": 2}]);
+        assert_eq!(
+            run("sort_by(.v) | map(.v)", input.clone()),
+            vec![crate::json!([1, 2, 3])]
+        );
+        assert_eq!(
+            run(
+                "group_by(.g) | map({g: .[0].g, total: map(.v) | add})",
+                input.clone()
+            ),
+            vec![crate::json!([{"g": "x", "total": 5}, {"g": "y", "total": 1}])]
+        );
+        assert_eq!(run("length", input.clone()), vec![crate::json!(3)]);
+        assert_eq!(
+            run(".[0] | keys", input.clone()),
+            vec![crate::json!(["g", "v"])]
+        );
+        assert_eq!(
+            run("map(.g) | unique | join(\"-\")", input),
+            vec![crate::json!("x-y")]
+        );
+        assert_eq!(
+            run("[3, 1, 2] | sort | reverse | max", Value::Null),
+            vec![crate::json!(3)]
+        );
+        assert_eq!(
+            run("\"12\" | tonumber | type", Value::Null),
+            vec![crate::json!("number")]
+        );
+        assert_eq!(
+            run("has(\"a\"), has(\"b\")", crate::json!({"a": 1})),
+            vec![crate::json!(true), crate::json!(false)]
+        );
+    }
+
+    #[test]
+    fn interpolate_strings() {
+        let input = crate::json!({"name": "ann", "tags": ["a"], "n": 1.5});
+        assert_eq!(
+            run("\"\\(.name) has \\(.tags) and \\(.n + 1)\"", input),
+            vec![crate::json!("ann has [\"a\"] and 2.5")]
+        );
+        assert_eq!(
+            run("\"nested \\(\"in \\(1 + 1)\")\"", Value::Null),
+            vec![crate::json!("nested in 2")]
+        );
+    }
+
+    #[test]
+    fn reject_bad_queries() {
+        assert_eq!(
+            Query::parse(".a |").unwrap_err().message,
+            "expected an expression, found end of query"
+        );
+        assert_eq!(
+            Query::parse("frobnicate(1)").unwrap_err().message,
+            "unknown function frobnicate/1"
+        );
+        assert!(Query::parse("{(.a)}").is_err());
+        assert!(Query::parse("\"open").is_err());
+        assert!(Query::parse("if . then 1").is_err());
+    }
+}
@@ -0,0 +1,446 @@
+use std::fmt::Display;
+
+use crate::{ParsingError, ParsonResult};
+
+use super::{
+    lexer::{decode_string, Lexeme, Lexer, Span, TokenKind},
+    spanned::{Spanned, SpannedValue},
+    token::Number,
+    JsonPointer, Value,
+};
+
+/// A syntax error found while parsing in recovery mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonError {
+    pub message: String,
+    pub span: Span,
+    /// Location of the placeholder that stands in for the broken part.
+    pub pointer: JsonPointer,
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.span.line, self.span.column, self.message
+        )
+    }
+}
+
+/// What could be parsed from a document, and everything that was wrong with it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveredJson {
+    /// The parsed value, with [`Value::Null`] placeholders for the broken parts.
+    pub value: Value,
+    pub errors: Vec<JsonError>,
+}
+
+/// Parses `source` without stopping at the first syntax error.
+///
+/// After an error the parser resynchronizes at the next comma or closing
+/// bracket of the container it is in, so every problem gets reported.
+pub fn parse_recovering(source: &str) -> RecoveredJson {
+    let (spanned, errors) = parse_with_errors(source);
+    RecoveredJson {
+        value: spanned.to_value(),
+        errors,
+    }
+}
+
+/// Parses `source` into a tree where every value and key carries its span.
+pub fn parse_spanned(source: &str) -> ParsonResult<Spanned<SpannedValue>> {
+    let (spanned, errors) = parse_with_errors(source);
+    match errors.into_iter().next() {
+        Some(error) => Err(ParsingError {
+            message: error.to_string(),
+        }),
+        None => Ok(spanned),
+    }
+}
+
+fn parse_with_errors(source: &str) -> (Spanned<SpannedValue>, Vec<JsonError>) {
+    let mut parser = RecoveringParser {
+        source,
+        lexemes: Lexer::new(source)
+            .filter(|lexeme| lexeme.kind != TokenKind::Whitespace)
+            .collect(),
+        pos: 0,
+        pointer: JsonPointer::default(),
+        errors: vec![],
+    };
+    let value = parser.parse_value();
+    if let Some(lexeme) = parser.peek() {
+        parser.error("unexpected data after the json value", lexeme.span);
+    }
+    (value, parser.errors)
+}
+
+struct RecoveringParser<'a> {
+    source: &'a str,
+    lexemes: Vec<Lexeme>,
+    pos: usize,
+    pointer: JsonPointer,
+    errors: Vec<JsonError>,
+}
+
+impl RecoveringParser<'_> {
+    fn peek(&self) -> Option<Lexeme> {
+        self.lexemes.get(self.pos).copied()
+    }
+
+    fn peek_kind(&self) -> Option<TokenKind> {
+        self.peek().map(|lexeme| lexeme.kind)
+    }
+
+    fn text(&self, lexeme: Lexeme) -> &str {
+        &self.source[lexeme.span.start..lexeme.span.end]
+    }
+
+    /// Span to blame when the input ends early.
+    fn end_span(&self) -> Span {
+        self.lexemes
+            .last()
+            .map(|lexeme| Span {
+                start: lexeme.span.end,
+                ..lexeme.span
+            })
+            .unwrap_or_default()
+    }
+
+    fn error(&mut self, message: &str, span: Span) {
+        self.errors.push(JsonError {
+            message: message.to_string(),
+            span,
+            pointer: self.pointer.clone(),
+        });
+    }
+
+    fn parse_value(&mut self) -> Spanned<SpannedValue> {
+        let Some(lexeme) = self.peek() else {
+            let span = self.end_span();
+            self.error("expected a value, found end of input", span);
+            return Spanned {
+                value: SpannedValue::Null,
+                span,
+            };
+        };
+        match lexeme.kind {
+            TokenKind::OpenCurlyBracket => return self.parse_object(),
+            TokenKind::OpenSquareBracket => return self.parse_array(),
+            TokenKind::CloseCurlyBracket
+            | TokenKind::CloseSquareBracket
+            | TokenKind::Comma
+            | TokenKind::Colon => {
+                // leave the token to the enclosing container
+                self.error("expected a value", lexeme.span);
+                return Spanned {
+                    value: SpannedValue::Null,
+                    span: Span {
+                        end: lexeme.span.start,
+                        ..lexeme.span
+                    },
+                };
+            }
+            _ => {}
+        }
+        self.pos += 1;
+        let text = self.text(lexeme);
+        let value = match lexeme.kind {
+            TokenKind::String => match decode_string(text) {
+                Ok(string) => SpannedValue::String(string),
+                Err(message) => {
+                    self.error(&message, lexeme.span);
+                    SpannedValue::Null
+                }
+            },
+            TokenKind::Number => {
+                SpannedValue::Number(Number::Float(text.parse().unwrap_or_default()))
+            }
+            TokenKind::Boolean => SpannedValue::Boolean(text == "true"),
+            TokenKind::Null => SpannedValue::Null,
+            _ => {
+                let message = format!("unexpected '{}'", text);
+                self.error(&message, lexeme.span);
+                SpannedValue::Null
+            }
+        };
+        Spanned {
+            value,
+            span: lexeme.span,
+        }
+    }
+
+    /// Span from `open` to the last token consumed.
+    fn span_from(&self, open: Span) -> Span {
+        Span {
+            end: self.lexemes[self.pos - 1].span.end,
+            ..open
+        }
+    }
+
+    fn parse_object(&mut self) -> Spanned<SpannedValue> {
+        let open = self.peek().expect("called on an open bracket");
+        self.pos += 1;
+        let mut members = vec![];
+        if self.peek_kind() == Some(TokenKind::CloseCurlyBracket) {
+            self.pos += 1;
+            return Spanned {
+                value: SpannedValue::Object(members),
+                span: self.span_from(open.span),
+            };
+        }
+        loop {
+            match self.peek() {
+                Some(lexeme) if lexeme.kind == TokenKind::String => {
+                    self.pos += 1;
+                    let key = match decode_string(self.text(lexeme)) {
+                        Ok(key) => key,
+                        Err(message) => {
+                            self.error(&message, lexeme.span);
+                            self.text(lexeme).to_string()
+                        }
+                    };
+                    self.pointer.push(key.as_str());
+                    let has_colon = match self.peek() {
+                        Some(colon) if colon.kind == TokenKind::Colon => {
+                            self.pos += 1;
+                            true
+                        }
+                        Some(other) if self.starts_value(other.kind) => {
+                            self.error("expected ':' after object key", other.span);
+                            false
+                        }
+                        other => {
+                            let span = other.map_or_else(|| self.end_span(), |l| l.span);
+                            self.error("expected ':' after object key", span);
+                            self.synchronize();
+                            false
+                        }
+                    };
+                    let value = if !self.at_separator() {
+                        self.parse_value()
+                    } else {
+                        if has_colon {
+                            let span = self.peek().map_or_else(|| self.end_span(), |l| l.span);
+                            self.error("expected a value", span);
+                        }
+                        Spanned {
+                            value: SpannedValue::Null,
+                            span: self.span_from(lexeme.span),
+                        }
+                    };
+                    members.push((
+                        Spanned {
+                            value: key,
+                            span: lexeme.span,
+                        },
+                        value,
+                    ));
+                    self.pointer.pop();
+                }
+                Some(lexeme) => {
+                    self.error("expected a string key", lexeme.span);
+                    self.synchronize();
+                }
+                None => {}
+            }
+
+            if !self.next_item(TokenKind::CloseCurlyBracket, open.span) {
+                break;
+            }
+        }
+        Spanned {
+            value: SpannedValue::Object(members),
+            span: self.span_from(open.span),
+        }
+    }
+
+    fn parse_array(&mut self) -> Spanned<SpannedValue> {
+        let open = self.peek().expect("called on an open bracket");
+        self.pos += 1;
+        let mut array = vec![];
+        if self.peek_kind() == Some(TokenKind::CloseSquareBracket) {
+            self.pos += 1;
+            return Spanned {
+                value: SpannedValue::Array(array),
+                span: self.span_from(open.span),
+            };
+        }
+        loop {
+            self.pointer.push(array.len().to_string());
+            let value = self.parse_value();
+            array.push(value);
+            self.pointer.pop();
+
+            if !self.next_item(TokenKind::CloseSquareBracket, open.span) {
+                break;
+            }
+        }
+        Spanned {
+            value: SpannedValue::Array(array),
+            span: self.span_from(open.span),
+        }
+    }
+
+    /// Moves past the separator after an item, returning whether another item follows.
+    fn next_item(&mut self, close: TokenKind, open: Span) -> bool {
+        let (close_char, container) = match close {
+            TokenKind::CloseCurlyBracket => ('}', "object"),
+            _ => (']', "array"),
+        };
+        loop {
+            match self.peek() {
+                Some(lexeme) if lexeme.kind == TokenKind::Comma => {
+                    self.pos += 1;
+                    if self.peek_kind() == Some(close) {
+                        self.error(&format!("trailing comma in {}", container), lexeme.span);
+                        self.pos += 1;
+                        return false;
+                    }
+                    return true;
+                }
+                Some(lexeme) if lexeme.kind == close => {
+                    self.pos += 1;
+                    return false;
+                }
+                Some(lexeme)
+                    if matches!(
+                        lexeme.kind,
+                        TokenKind::CloseCurlyBracket | TokenKind::CloseSquareBracket
+                    ) =>
+                {
+                    // most likely a missing bracket, let the enclosing container close
+                    self.error(&format!("expected '{}'", close_char), lexeme.span);
+                    return false;
+                }
+                Some(lexeme) => {
+                    self.error(&format!("expected ',' or '{}'", close_char), lexeme.span);
+                    self.synchronize();
+                }
+                None => {
+                    self.error(&format!("unterminated {}", container), open);
+                    return false;
+                }
+            }
+        }
+    }
+
+    fn starts_value(&self, kind: TokenKind) -> bool {
+        !matches!(
+            kind,
+            TokenKind::CloseCurlyBracket
+                | TokenKind::CloseSquareBracket
+                | TokenKind::Comma
+                | TokenKind::Colon
+        )
+    }
+
+    fn at_separator(&self) -> bool {
+        matches!(
+            self.peek_kind(),
+            Some(TokenKind::Comma | TokenKind::CloseCurlyBracket | TokenKind::CloseSquareBracket)
+        )
+    }
+
+    /// Skips ahead to the next comma or closing bracket of the current container.
+    fn synchronize(&mut self) {
+        let mut depth = 0usize;
+        while let Some(kind) = self.peek_kind() {
+            match kind {
+                TokenKind::OpenCurlyBracket | TokenKind::OpenSquareBracket => depth += 1,
+                TokenKind::CloseCurlyBracket | TokenKind::CloseSquareBracket if depth == 0 => {
+                    return
+                }
+                TokenKind::CloseCurlyBracket | TokenKind::CloseSquareBracket => depth -= 1,
+                TokenKind::Comma if depth == 0 => return,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{JsonPointer, Value};
+
+    use super::parse_recovering;
+
+    #[test]
+    fn valid_input_has_no_errors() {
+        let recovered = parse_recovering(r#"{"a": [1, "b", {"c": null}], "d": true}"#);
+        assert!(recovered.errors.is_empty());
+        assert_eq!(
+            recovered.value,
+            crate::json!({"a": [1, "b", {"c": null}], "d": true})
+        );
+    }
+
+    #[test]
+    fn collect_every_error() {
+        let source = "{\n  \"a\": tru,\n  \"b\": [1, 2,, 3],\n  \"c\" 4,\n  \"d\": \"ok\",\n  \"e\": [1, 2}\n";
+        let recovered = parse_recovering(source);
+
+        let messages = recovered
+            .errors
+            .iter()
+            .map(|error| (error.span.line, error.pointer.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                (2, "/a".to_string()),
+                (3, "/b/2".to_string()),
+                (4, "/c".to_string()),
+                (6, "/e".to_string()),
+            ]
+        );
+        assert_eq!(
+            recovered.errors[0].to_string(),
+            "line 2, column 8: unexpected 'tru'"
+        );
+
+        let value = recovered.value;
+        assert_eq!(
+            value.pointer(&JsonPointer::parse("/d").unwrap()),
+            Some(&Value::String("ok".to_string()))
+        );
+        assert_eq!(
+            value.pointer(&JsonPointer::parse("/b").unwrap()),
+            Some(&crate::json!([1, 2, null, 3]))
+        );
+        assert_eq!(
+            value.pointer(&JsonPointer::parse("/a").unwrap()),
+            Some(&Value::Null)
+        );
+        assert_eq!(
+            value.pointer(&JsonPointer::parse("/c").unwrap()),
+            Some(&crate::json!(4))
+        );
+    }
+
+    #[test]
+    fn report_early_end_and_trailing_data() {
+        let recovered = parse_recovering("");
+        assert_eq!(recovered.errors.len(), 1);
+
+        let recovered = parse_recovering("[1, 2] 3");
+        assert_eq!(recovered.value, crate::json!([1, 2]));
+        assert_eq!(
+            recovered.errors[0].message,
+            "unexpected data after the json value"
+        );
+    }
+
+    #[test]
+    fn report_missing_member_value() {
+        let recovered = parse_recovering("{\"a\": 1, \"b\": }");
+        assert_eq!(recovered.value, crate::json!({"a": 1, "b": null}));
+        assert_eq!(recovered.errors.len(), 1);
+        assert_eq!(
+            recovered.errors[0].to_string(),
+            "line 1, column 15: expected a value"
+        );
+    }
+}
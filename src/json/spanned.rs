@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use super::{lexer::Span, token::Number, JsonPointer, Value};
+
+/// A value together with the location it was parsed from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub span: Span,
+}
+
+/// A json tree in which every value, and every object key, carries its [`Span`].
+///
+/// Object members keep their source order, duplicates included; lookups pick
+/// the last one like [`Value::Object`] does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpannedValue {
+    String(String),
+    Number(Number),
+    Object(Vec<(Spanned<String>, Spanned<SpannedValue>)>),
+    Null,
+    Boolean(bool),
+    Array(Vec<Spanned<SpannedValue>>),
+}
+
+/// An object key, absent for array elements, and the value it leads to.
+type Member<'a> = (Option<&'a Spanned<String>>, &'a Spanned<SpannedValue>);
+
+impl Spanned<SpannedValue> {
+    /// Drops the spans, giving back a plain [`Value`].
+    pub fn to_value(&self) -> Value {
+        match &self.value {
+            SpannedValue::String(string) => Value::String(string.clone()),
+            SpannedValue::Number(number) => Value::Number(number.clone()),
+            SpannedValue::Null => Value::Null,
+            SpannedValue::Boolean(boolean) => Value::Boolean(*boolean),
+            SpannedValue::Array(array) => Value::Array(array.iter().map(Self::to_value).collect()),
+            SpannedValue::Object(members) => Value::Object(
+                members
+                    .iter()
+                    .map(|(key, value)| (key.value.clone(), value.to_value()))
+                    .collect::<HashMap<String, Value>>(),
+            ),
+        }
+    }
+
+    /// Returns the node found at `pointer`, if any.
+    pub fn pointer(&self, pointer: &JsonPointer) -> Option<&Spanned<SpannedValue>> {
+        pointer.segments().iter().try_fold(self, |node, segment| {
+            node.member(segment).map(|(_, value)| value)
+        })
+    }
+
+    /// Returns the span of the value found at `pointer`.
+    pub fn span_of(&self, pointer: &JsonPointer) -> Option<Span> {
+        self.pointer(pointer).map(|node| node.span)
+    }
+
+    /// Returns the span of the object key that leads to `pointer`.
+    pub fn key_span_of(&self, pointer: &JsonPointer) -> Option<Span> {
+        let (last, parents) = pointer.segments().split_last()?;
+        let parent = parents.iter().try_fold(self, |node, segment| {
+            node.member(segment).map(|(_, value)| value)
+        })?;
+        parent
+            .member(last)
+            .and_then(|(key, _)| key)
+            .map(|key| key.span)
+    }
+
+    fn member(&self, segment: &str) -> Option<Member<'_>> {
+        match &self.value {
+            SpannedValue::Object(members) => members
+                .iter()
+                .rev()
+                .find(|(key, _)| key.value == segment)
+                .map(|(key, value)| (Some(key), value)),
+            SpannedValue::Array(array) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(|idx| array.get(idx))
+                .map(|value| (None, value)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{lexer::Span, JsonPointer};
+
+    use super::SpannedValue;
+
+    #[test]
+    fn spans_of_values_and_keys() {
+        let source = "{\n  \"name\": \"parson\",\n  \"list\": [1, true]\n}";
+        let spanned = crate::json::parse_spanned(source).unwrap();
+
+        assert_eq!(spanned.span.start, 0);
+        assert_eq!(spanned.span.end, source.len());
+
+        let pointer = JsonPointer::parse("/list/1").unwrap();
+        assert_eq!(
+            spanned.span_of(&pointer),
+            Some(Span {
+                start: 36,
+                end: 40,
+                line: 3,
+                column: 15
+            })
+        );
+        assert_eq!(&source[36..40], "true");
+
+        let key = spanned
+            .key_span_of(&JsonPointer::parse("/name").unwrap())
+            .unwrap();
+        assert_eq!(&source[key.start..key.end], "\"name\"");
+        assert_eq!((key.line, key.column), (2, 3));
+
+        assert!(spanned
+            .span_of(&JsonPointer::parse("/list/2").unwrap())
+            .is_none());
+        assert!(matches!(
+            spanned
+                .pointer(&JsonPointer::parse("/name").unwrap())
+                .unwrap()
+                .value,
+            SpannedValue::String(..)
+        ));
+        assert_eq!(
+            spanned.to_value(),
+            crate::json!({"name": "parson", "list": [1, true]})
+        );
+    }
+
+    #[test]
+    fn fail_on_first_error() {
+        let error = crate::json::parse_spanned("[1,\n 2,, 3]").unwrap_err();
+        assert_eq!(error.message, "line 2, column 4: expected a value");
+    }
+}
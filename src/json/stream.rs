@@ -0,0 +1,171 @@
+use std::collections::VecDeque;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::{ParsingError, ParsonResult};
+
+use super::{JsonEvent, JsonPushParser, JsonValueBuilder, Value};
+
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Feeds `reader` to a [`JsonPushParser`] chunk by chunk.
+struct EventReader<R> {
+    reader: R,
+    parser: Option<JsonPushParser>,
+    events: VecDeque<JsonEvent>,
+    buf: Vec<u8>,
+}
+
+impl<R: AsyncRead + Unpin> EventReader<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            parser: Some(JsonPushParser::new()),
+            events: VecDeque::new(),
+            buf: vec![0; CHUNK_SIZE],
+        }
+    }
+
+    async fn next_event(&mut self) -> ParsonResult<Option<JsonEvent>> {
+        while self.events.is_empty() {
+            let Some(parser) = self.parser.as_mut() else {
+                return Ok(None);
+            };
+            let read = self
+                .reader
+                .read(&mut self.buf)
+                .await
+                .map_err(|e| ParsingError {
+                    message: format!("failed to read json input: {}", e),
+                })?;
+            if read == 0 {
+                let parser = self.parser.take().expect("parser checked above");
+                self.events.extend(parser.finish()?);
+            } else {
+                self.events.extend(parser.feed(&self.buf[..read])?);
+            }
+        }
+        Ok(self.events.pop_front())
+    }
+}
+
+pub async fn parse_async<R: AsyncRead + Unpin>(reader: R) -> ParsonResult<Value> {
+    let mut events = EventReader::new(reader);
+    let mut builder = JsonValueBuilder::new();
+    let mut value = None;
+    while let Some(event) = events.next_event().await? {
+        if let Some(completed) = builder.push(event) {
+            if value.replace(completed).is_some() {
+                return Err(ParsingError {
+                    message: "unexpected data after the json value".to_string(),
+                });
+            }
+        }
+    }
+    value.ok_or_else(|| ParsingError {
+        message: "no json value in input".to_string(),
+    })
+}
+
+/// Yields the elements of a top-level json array as they are read.
+pub struct JsonArrayStream<R> {
+    events: EventReader<R>,
+    builder: JsonValueBuilder,
+    depth: usize,
+    done: bool,
+}
+
+impl<R: AsyncRead + Unpin> JsonArrayStream<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            events: EventReader::new(reader),
+            builder: JsonValueBuilder::new(),
+            depth: 0,
+            done: false,
+        }
+    }
+
+    /// Returns the next element, or `None` once the array is closed.
+    pub async fn next_element(&mut self) -> Option<ParsonResult<Value>> {
+        if self.done {
+            return None;
+        }
+        let element = self.read_element().await;
+        if !matches!(element, Some(Ok(..))) {
+            self.done = true;
+        }
+        element
+    }
+
+    async fn read_element(&mut self) -> Option<ParsonResult<Value>> {
+        loop {
+            let event = match self.events.next_event().await {
+                Ok(Some(event)) => event,
+                Ok(None) => {
+                    return Some(Err(ParsingError {
+                        message: "expected a top-level json array".to_string(),
+                    }))
+                }
+                Err(error) => return Some(Err(error)),
+            };
+            match (&event, self.depth) {
+                (JsonEvent::StartArray, 0) => {
+                    self.depth = 1;
+                    continue;
+                }
+                (_, 0) => {
+                    return Some(Err(ParsingError {
+                        message: "expected a top-level json array".to_string(),
+                    }))
+                }
+                (JsonEvent::EndArray, 1) => return None,
+                (JsonEvent::StartArray | JsonEvent::StartObject, _) => self.depth += 1,
+                (JsonEvent::EndArray | JsonEvent::EndObject, _) => self.depth -= 1,
+                _ => {}
+            }
+            if let Some(element) = self.builder.push(event) {
+                return Some(Ok(element));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{token::Number, JsonPointer, Value};
+
+    use super::{parse_async, JsonArrayStream};
+
+    #[tokio::test]
+    async fn parse_from_async_reader() {
+        let value = parse_async(&br#"{"a": [1, 2]}"#[..]).await.unwrap();
+        assert_eq!(
+            value.pointer(&JsonPointer::parse("/a/1").unwrap()),
+            Some(&Value::Number(Number::Float(2.0)))
+        );
+
+        assert!(parse_async(&b"[1] [2]"[..]).await.is_err());
+
+        let text = r#"{"a": "x\ny\"z", "b": ["\u00e9\\", "\ud83d\ude00"]}"#;
+        assert_eq!(
+            parse_async(text.as_bytes()).await.unwrap(),
+            crate::Parson::parse_json(text).unwrap()
+        );
+        assert!(parse_async(&b"  "[..]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn stream_array_elements() {
+        let mut stream = JsonArrayStream::new(&br#"[{"id": 1}, [true], "x"]"#[..]);
+        let mut elements = vec![];
+        while let Some(element) = stream.next_element().await {
+            elements.push(element.unwrap());
+        }
+        assert_eq!(elements.len(), 3);
+        assert_eq!(elements[1], Value::Array(vec![Value::Boolean(true)]));
+
+        let mut stream = JsonArrayStream::new(&br#"{"id": 1}"#[..]);
+        assert!(stream.next_element().await.unwrap().is_err());
+        assert!(stream.next_element().await.is_none());
+    }
+}
@@ -2,7 +2,9 @@ use std::{iter::Peekable, str::Chars};
 
 use crate::{ParsingError, ParsonResult};
 
-#[derive(Debug, Clone)]
+use super::lexer::decode_string;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Number {
     Float(f64),
 }
@@ -38,17 +40,18 @@ impl Tokenizer {
         &self.tokens
     }
 
+    /// Reads a string up to its closing quote and decodes its escapes.
     fn parse_string(peekable_chars: &mut Peekable<&mut Chars>) -> ParsonResult<Token> {
-        let mut string_value = String::new();
+        let mut raw = String::from('"');
         let mut complete = false;
-        let mut previous_value: Option<char> = None;
+        let mut escaped = false;
         for next_value in peekable_chars.by_ref() {
-            if next_value != '"' {
-                string_value.push(next_value);
-                previous_value = Some(next_value);
-            } else if previous_value == Some('\\') {
-                string_value.push(next_value);
-            } else {
+            raw.push(next_value);
+            if escaped {
+                escaped = false;
+            } else if next_value == '\\' {
+                escaped = true;
+            } else if next_value == '"' {
                 complete = true;
                 break;
             }
@@ -59,6 +62,7 @@ impl Tokenizer {
                 message: "Not a valid json".to_string(),
             });
         }
+        let string_value = decode_string(&raw).map_err(|message| ParsingError { message })?;
         Ok(Token::StringValue(string_value))
     }
 
@@ -66,7 +70,7 @@ impl Tokenizer {
         let mut string_value = val.to_string();
 
         while let Some(next_value) = peekable_chars.peek() {
-            if *next_value == ',' {
+            if matches!(*next_value, ',' | '}' | ']') || next_value.is_whitespace() {
                 break;
             }
 
@@ -74,6 +78,8 @@ impl Tokenizer {
                 || *next_value == '.'
                 || *next_value == 'E'
                 || *next_value == 'e'
+                || *next_value == '-'
+                || *next_value == '+'
             {
                 string_value.push(*next_value);
             } else {
@@ -138,7 +144,11 @@ impl Tokenizer {
                 val @ ('t' | 'f') => Self::parse_boolean(val, &mut peekable_chars)?,
                 'n' => Self::parse_null(&mut peekable_chars)?,
                 _ => {
-                    // this is mainly whitespace can be ignored
+                    // this is mainly whitespace can be ignored, a run of it
+                    // is kept as a single token
+                    if let Some(Token::Whitespace) = tokens.last() {
+                        continue;
+                    }
                     Token::Whitespace
                 }
             };
@@ -171,7 +181,7 @@ mod tests {
 
         let tokens = tokenizer.tokens;
 
-        assert_eq!(tokens.len(), 8);
+        assert_eq!(tokens.len(), 7);
 
         assert!(matches!(tokens[0], Token::OpenSquareBracket));
         assert!(matches!(tokens[1], Token::CloseSquareBracket));
@@ -193,8 +203,8 @@ mod tests {
         };
         assert_eq!(value, "somethingelse");
 
+        // the two trailing spaces are a single token
         assert!(matches!(tokens[6], Token::Whitespace));
-        assert!(matches!(tokens[7], Token::Whitespace));
     }
 
     #[test]
@@ -278,7 +288,7 @@ mod tests {
 
         let tokens = tokenizer.tokens;
 
-        assert_eq!(tokens.len(), 12);
+        assert_eq!(tokens.len(), 11);
 
         assert!(matches!(tokens[0], Token::NumberValue(Number::Float(..))));
 
@@ -291,42 +301,41 @@ mod tests {
         assert_eq!(*value, 1.234234);
         assert!(matches!(tokens[1], Token::Comma));
         assert!(matches!(tokens[2], Token::Whitespace));
-        assert!(matches!(tokens[3], Token::Whitespace));
-        assert!(matches!(tokens[4], Token::NumberValue(Number::Float(..))));
+        assert!(matches!(tokens[3], Token::NumberValue(Number::Float(..))));
 
-        let value = if let Token::NumberValue(Number::Float(val)) = &tokens[4] {
+        let value = if let Token::NumberValue(Number::Float(val)) = &tokens[3] {
             val
         } else {
             panic!("This value must be a number, we asserted it one line above");
         };
 
         assert_eq!(*value, 23.0);
-        assert!(matches!(tokens[5], Token::Comma));
-        assert!(matches!(tokens[6], Token::Whitespace));
+        assert!(matches!(tokens[4], Token::Comma));
+        assert!(matches!(tokens[5], Token::Whitespace));
 
-        assert!(matches!(tokens[7], Token::NumberValue(Number::Float(..))));
+        assert!(matches!(tokens[6], Token::NumberValue(Number::Float(..))));
 
-        let value = if let Token::NumberValue(Number::Float(val)) = &tokens[7] {
+        let value = if let Token::NumberValue(Number::Float(val)) = &tokens[6] {
             val
         } else {
             panic!("This value must be a number, we asserted it one line above");
         };
         assert_eq!(*value, 4.4);
-        assert!(matches!(tokens[8], Token::Comma));
+        assert!(matches!(tokens[7], Token::Comma));
 
-        assert!(matches!(tokens[9], Token::NumberValue(Number::Float(..))));
+        assert!(matches!(tokens[8], Token::NumberValue(Number::Float(..))));
 
-        let value = if let Token::NumberValue(Number::Float(val)) = &tokens[9] {
+        let value = if let Token::NumberValue(Number::Float(val)) = &tokens[8] {
             val
         } else {
             panic!("This value must be a number, we asserted it one line above");
         };
         assert_eq!(*value, -0.23);
 
-        assert!(matches!(tokens[10], Token::Comma));
-        assert!(matches!(tokens[11], Token::NumberValue(Number::Float(..))));
+        assert!(matches!(tokens[9], Token::Comma));
+        assert!(matches!(tokens[10], Token::NumberValue(Number::Float(..))));
 
-        let value = if let Token::NumberValue(Number::Float(val)) = &tokens[11] {
+        let value = if let Token::NumberValue(Number::Float(val)) = &tokens[10] {
             val
         } else {
             panic!("This value must be a number, we asserted it one line above");
@@ -334,6 +343,20 @@ mod tests {
         assert_eq!(*value, 23.43);
     }
 
+    #[test]
+    fn tokenize_number_before_closing_bracket() {
+        let string = r#"{"a": 1e-3}[2 ]"#;
+        let tokenizer = Tokenizer::new(&mut string.chars());
+
+        assert!(tokenizer.is_ok());
+
+        let tokens = tokenizer.unwrap().tokens;
+        assert!(matches!(tokens[4], Token::NumberValue(Number::Float(n)) if n == 0.001));
+        assert!(matches!(tokens[5], Token::CloseCurlyBracket));
+        assert!(matches!(tokens[7], Token::NumberValue(Number::Float(n)) if n == 2.0));
+        assert!(matches!(tokens[8], Token::Whitespace));
+    }
+
     #[test]
     fn unsuccessfully_tokenize_number() {
         let string = r#"34:22"#;
@@ -401,7 +424,24 @@ mod tests {
         } else {
             panic!("This value must be a string, we asserted it one line above");
         };
-        let expected = r#"test\"_something"#.to_string();
+        let expected = "test\"_something".to_string();
         assert_eq!(*value, expected);
     }
+
+    #[test]
+    fn decode_escapes_in_strings() {
+        let string = r#"["a\\", "\u00e9\n\ud83d\ude00"]"#;
+        let tokens = Tokenizer::new(&mut string.chars()).unwrap().tokens;
+        let strings = tokens
+            .iter()
+            .filter_map(|token| match token {
+                Token::StringValue(value) => Some(value.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(strings, vec!["a\\", "é\n😀"]);
+
+        let error = Tokenizer::new(&mut r#""\q""#.chars()).unwrap_err();
+        assert_eq!(error.message, "invalid escape sequence \\q");
+    }
 }
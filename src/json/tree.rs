@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use crate::{ParsingError, ParsonResult};
+
+use super::{
+    token::{Number, Token},
+    Value,
+};
+
+/// Receives the values found while walking the tokenizer output, so the plain,
+/// interned and arena trees are all built with the same parsing rules.
+pub(crate) trait TreeBuilder {
+    type Key;
+    type Node;
+
+    fn key(&mut self, key: &str) -> Self::Key;
+    fn string(&mut self, string: String) -> Self::Node;
+    fn number(&mut self, number: Number) -> Self::Node;
+    fn boolean(&mut self, boolean: bool) -> Self::Node;
+    fn null(&mut self) -> Self::Node;
+    /// Members come in document order; a repeated key replaces the earlier one.
+    fn object(&mut self, members: Vec<(Self::Key, Self::Node)>) -> Self::Node;
+    fn array(&mut self, elements: Vec<Self::Node>) -> Self::Node;
+}
+
+/// Builds plain [`Value`]s.
+pub(crate) struct ValueTree;
+
+impl TreeBuilder for ValueTree {
+    type Key = String;
+    type Node = Value;
+
+    fn key(&mut self, key: &str) -> String {
+        key.to_string()
+    }
+
+    fn string(&mut self, string: String) -> Value {
+        Value::String(string)
+    }
+
+    fn number(&mut self, number: Number) -> Value {
+        Value::Number(number)
+    }
+
+    fn boolean(&mut self, boolean: bool) -> Value {
+        Value::Boolean(boolean)
+    }
+
+    fn null(&mut self) -> Value {
+        Value::Null
+    }
+
+    fn object(&mut self, members: Vec<(String, Value)>) -> Value {
+        Value::Object(members.into_iter().collect::<HashMap<_, _>>())
+    }
+
+    fn array(&mut self, elements: Vec<Value>) -> Value {
+        Value::Array(elements)
+    }
+}
+
+pub(crate) fn build<B: TreeBuilder>(
+    tokens: &mut impl Iterator<Item = Token>,
+    builder: &mut B,
+) -> ParsonResult<B::Node> {
+    let failed = || ParsingError {
+        message: "Failed to parse object. Is it a valid one?".to_string(),
+    };
+    let mut value = None;
+    let mut empty = true;
+    while let Some(token) = tokens.next() {
+        empty = false;
+        // Whitespace around the value is fine, but not in place of one or
+        // after it.
+        if let Token::Whitespace = token {
+            continue;
+        }
+        if value.is_some() {
+            return Err(ParsingError {
+                message: "unexpected data after the json value".to_string(),
+            });
+        }
+        value = Some(build_value(token, tokens, builder).ok_or_else(failed)?);
+    }
+    match value {
+        Some(value) => Ok(value),
+        None if empty => Ok(builder.null()),
+        None => Err(failed()),
+    }
+}
+
+/// Builds the value starting at `token`, or returns `None` if it does not
+/// start one.
+fn build_value<B: TreeBuilder>(
+    token: Token,
+    tokens: &mut impl Iterator<Item = Token>,
+    builder: &mut B,
+) -> Option<B::Node> {
+    Some(match token {
+        Token::OpenCurlyBracket => build_object(tokens, builder),
+        Token::OpenSquareBracket => build_array(tokens, builder),
+        Token::StringValue(string) => builder.string(string),
+        Token::NumberValue(number) => builder.number(number),
+        Token::Boolean(boolean) => builder.boolean(boolean),
+        Token::Null => builder.null(),
+        _ => return None,
+    })
+}
+
+fn build_object<B: TreeBuilder>(
+    tokens: &mut impl Iterator<Item = Token>,
+    builder: &mut B,
+) -> B::Node {
+    let mut is_key = true;
+    let mut current_key: Option<String> = None;
+    let mut members = vec![];
+
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::CloseCurlyBracket => break,
+            Token::Comma => is_key = true,
+            Token::Colon => is_key = false,
+            Token::StringValue(string) if is_key => current_key = Some(string),
+            token => {
+                // Without a key, the tokens of a nested value are skipped one
+                // by one rather than as a whole.
+                let Some(ref key) = current_key else {
+                    continue;
+                };
+                if let Some(node) = build_value(token, tokens, builder) {
+                    members.push((builder.key(key), node));
+                }
+            }
+        }
+    }
+    builder.object(members)
+}
+
+fn build_array<B: TreeBuilder>(
+    tokens: &mut impl Iterator<Item = Token>,
+    builder: &mut B,
+) -> B::Node {
+    let mut elements = vec![];
+    while let Some(token) = tokens.next() {
+        if let Token::CloseSquareBracket = token {
+            break;
+        }
+        if let Some(node) = build_value(token, tokens, builder) {
+            elements.push(node);
+        }
+    }
+    builder.array(elements)
+}
@@ -1,8 +1,8 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display};
 
-use crate::{json::token::Number, ParsingError};
+use crate::{json::token::Number, DateFormat, ParsingError, Temporal};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     String(String),
     Number(Number),
@@ -12,6 +12,142 @@ pub enum Value {
     Array(Vec<Value>),
 }
 
+impl Display for Value {
+    /// Writes the value as compact json, with object keys sorted.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Boolean(boolean) => write!(f, "{}", boolean),
+            Value::Number(Number::Float(number)) if number.is_finite() => write!(f, "{}", number),
+            Value::Number(..) => write!(f, "null"),
+            Value::String(string) => write_escaped(string, f),
+            Value::Array(array) => {
+                write!(f, "[")?;
+                for (idx, item) in array.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(object) => {
+                let mut keys = object.keys().collect::<Vec<_>>();
+                keys.sort();
+                write!(f, "{{")?;
+                for (idx, key) in keys.into_iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_escaped(key, f)?;
+                    write!(f, ":{}", object[key])?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+pub(crate) fn write_escaped(string: &str, f: &mut impl std::fmt::Write) -> std::fmt::Result {
+    f.write_char('"')?;
+    for character in string.chars() {
+        match character {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\u{08}' => f.write_str("\\b")?,
+            '\u{0C}' => f.write_str("\\f")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            '\u{00}'..='\u{1F}' => write!(f, "\\u{:04x}", character as u32)?,
+            _ => f.write_char(character)?,
+        }
+    }
+    f.write_char('"')
+}
+
+macro_rules! impl_from_number {
+    ($($number:ty),*) => {
+        $(
+            /// Json numbers are held as `f64`, so integers beyond ±2^53, which
+            /// `i64`, `u64`, `i128` and the like can hold, are rounded to the
+            /// nearest `f64`.
+            impl From<$number> for Value {
+                fn from(value: $number) -> Self {
+                    Value::Number(Number::Float(value as f64))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_number!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Boolean(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<&String> for Value {
+    fn from(value: &String) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(value: Vec<T>) -> Self {
+        Value::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Clone + Into<Value>> From<&[T]> for Value {
+    fn from(value: &[T]) -> Self {
+        Value::Array(value.iter().cloned().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Value>> From<HashMap<String, T>> for Value {
+    fn from(value: HashMap<String, T>) -> Self {
+        Value::Object(
+            value
+                .into_iter()
+                .map(|(key, value)| (key, value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl Value {
+    /// Reads a string value with the first of `formats` that fits, e.g.
+    /// [`DateFormat::defaults`]. Json has no dates, so they are only looked
+    /// for when asked.
+    pub fn as_temporal(&self, formats: &[DateFormat]) -> Option<Temporal> {
+        match self {
+            Value::String(string) => formats.iter().find_map(|format| format.parse(string)),
+            _ => None,
+        }
+    }
+}
+
 impl TryFrom<Value> for i64 {
     type Error = ParsingError;
     fn try_from(value: Value) -> Result<Self, Self::Error> {
@@ -88,10 +224,31 @@ impl TryFrom<Value> for Vec<Value> {
 mod tests {
     use std::collections::HashMap;
 
-    use crate::{json::token::Number, ParsonResult};
+    use crate::{json::token::Number, Date, DateFormat, ParsonResult, Temporal};
 
     use super::Value;
 
+    #[test]
+    fn read_dates_from_strings() {
+        let formats = DateFormat::defaults();
+        assert_eq!(
+            Value::from("22.5.2021").as_temporal(&formats),
+            Some(Temporal::Date(Date::new(2021, 5, 22).unwrap()))
+        );
+        assert_eq!(
+            Value::from("2021-05-22T20:05:00+02:00")
+                .as_temporal(&formats)
+                .map(|temporal| temporal.to_string()),
+            Some("2021-05-22T20:05:00+02:00".to_string())
+        );
+        assert_eq!(Value::from("soon").as_temporal(&formats), None);
+        assert_eq!(Value::from(20210522).as_temporal(&formats), None);
+        assert_eq!(
+            Value::from("5/22/2021").as_temporal(&[DateFormat::iso_date()]),
+            None
+        );
+    }
+
     #[test]
     fn test_conversions() {
         let value = bool::try_from(Value::Boolean(false));
@@ -125,4 +282,32 @@ mod tests {
             .get("test")
             .is_some_and(|k| matches!(k, Value::Boolean(true))));
     }
+
+    #[test]
+    fn test_from_rust_values() {
+        assert_eq!(Value::from(3u8), Value::Number(Number::Float(3.0)));
+        assert_eq!(Value::from(-2i64), Value::Number(Number::Float(-2.0)));
+        assert_eq!(Value::from(1.5f32), Value::Number(Number::Float(1.5)));
+        assert_eq!(
+            Value::from(9_007_199_254_740_993u64),
+            Value::Number(Number::Float(9_007_199_254_740_992.0))
+        );
+        assert_eq!(Value::from("a"), Value::String("a".to_string()));
+        assert_eq!(Value::from(None::<bool>), Value::Null);
+        assert_eq!(Value::from(Some(true)), Value::Boolean(true));
+        assert_eq!(
+            Value::from(&[1, 2][..]),
+            Value::Array(vec![
+                Value::Number(Number::Float(1.0)),
+                Value::Number(Number::Float(2.0))
+            ])
+        );
+        assert_eq!(
+            Value::from(HashMap::from([("k".to_string(), vec!["v"])])),
+            Value::Object(HashMap::from([(
+                "k".to_string(),
+                Value::Array(vec![Value::String("v".to_string())])
+            )]))
+        );
+    }
 }
@@ -1,9 +1,35 @@
 mod csv;
-use std::collections::HashMap;
+mod datetime;
+mod encoding;
+mod file;
+use std::{borrow::Cow, collections::HashMap, path::Path, str::from_utf8};
 
-use csv::{CsvParser, Value as CsvValue};
+pub use csv::{
+    CsvColumn, CsvDialect, CsvDuplicateHeaders, CsvEncoding, CsvEscape, CsvHeader, CsvOptions,
+    CsvParser, CsvRecord, CsvSchema, CsvTerminator, CsvType, Number as CsvNumber,
+    Value as CsvValue,
+};
 mod json;
-use json::{JsonParser, Value as JsonValue};
+pub use json::{
+    render_diff, DiffOptions, Difference, JsonDocument, JsonError, JsonEvent, JsonPointer,
+    JsonPushParser, JsonValueBuilder, RecoveredJson, Span, Spanned, SpannedValue,
+};
+
+#[cfg(feature = "async")]
+pub use csv::CsvRecordStream;
+pub use datetime::{Date, DateFormat, DateTime, Temporal, Time};
+#[cfg(feature = "async")]
+pub use json::JsonArrayStream;
+pub use json::Query as JsonQuery;
+pub use json::Value as JsonValue;
+pub use json::{
+    ArenaValue as JsonArenaValue, InternedValue as JsonInternedValue, Interner as JsonInterner,
+    JsonArena,
+};
+use json::{JsonParser, PathExtractor};
+pub use json::{Lexeme as JsonLexeme, Lexer as JsonLexer, TokenKind as JsonTokenKind};
+#[cfg(feature = "async")]
+use tokio::io::AsyncRead;
 
 #[derive(Debug, Clone)]
 pub struct ParsingError {
@@ -21,18 +47,120 @@ impl Parson {
         json_parser.parse()
     }
 
+    /// Parses json from raw bytes. UTF-8 (with or without a byte order mark),
+    /// UTF-16 and UTF-32 input is detected and transcoded.
     pub fn parse_json_with_bytes(bytes: &[u8]) -> ParsonResult<JsonValue> {
         let json_parser = JsonParser::new(bytes)?;
         json_parser.parse()
     }
 
+    /// Decodes json bytes into text the way [`Parson::parse_json_with_bytes`]
+    /// does, for the parsers that take text.
+    pub fn decode_json(bytes: &[u8]) -> ParsonResult<Cow<'_, str>> {
+        let invalid = || ParsingError {
+            message: "input is not valid utf-8".to_string(),
+        };
+        match encoding::to_utf8(bytes)? {
+            Cow::Borrowed(bytes) => from_utf8(bytes).map(Cow::Borrowed).map_err(|_| invalid()),
+            Cow::Owned(bytes) => String::from_utf8(bytes)
+                .map(Cow::Owned)
+                .map_err(|_| invalid()),
+        }
+    }
+
+    /// Parses the json file at `path`, memory-mapping it rather than reading
+    /// it into a buffer when possible. Errors start with the path.
+    pub fn parse_json_file(path: impl AsRef<Path>) -> ParsonResult<JsonValue> {
+        let path = path.as_ref();
+        let bytes = file::read(path)?;
+        Self::parse_json_with_bytes(&bytes).map_err(|error| file::file_error(path, error.message))
+    }
+
+    /// Parses a top-level json array on several threads: a quick scan splits it
+    /// at element boundaries and the elements are parsed in parallel. Gives the
+    /// same value, or the same error, as [`Parson::parse_json_with_bytes`].
+    #[cfg(feature = "rayon")]
+    pub fn parse_json_parallel(bytes: &[u8]) -> ParsonResult<JsonValue> {
+        json::parse_parallel(bytes)
+    }
+
+    /// Parses json from raw bytes so that every repeated object key shares one
+    /// allocation. Use a [`JsonInterner`] to also share short string values,
+    /// or keys across several documents.
+    pub fn parse_json_interned(bytes: &[u8]) -> ParsonResult<JsonInternedValue> {
+        JsonInterner::new().parse(bytes)
+    }
+
+    /// Parses json from raw bytes into a [`JsonArena`], which keeps the whole
+    /// tree in a few buffers and frees it at once.
+    pub fn parse_json_arena(bytes: &[u8]) -> ParsonResult<JsonArena> {
+        JsonArena::parse(bytes)
+    }
+
+    /// Parses `json_string` without stopping at the first syntax error, returning
+    /// the partial value along with every error found and its position.
+    pub fn parse_json_recovering(json_string: &str) -> RecoveredJson {
+        json::parse_recovering(json_string)
+    }
+
+    /// Parses `json_string` into a tree where every value and object key carries
+    /// its byte offsets, line and column, to point users at the exact location
+    /// of a value in their file.
+    pub fn parse_json_spanned(json_string: &str) -> ParsonResult<Spanned<SpannedValue>> {
+        json::parse_spanned(json_string)
+    }
+
+    /// Extracts the values at the given JSON Pointers or dotted paths in a single
+    /// pass over `bytes`, skipping every subtree that is not on one of them.
+    /// The result has one entry per path, `None` when the path is absent.
+    /// Reading stops once every path is found, so input after the last match
+    /// is not validated.
+    pub fn extract_json(bytes: &[u8], paths: &[&str]) -> ParsonResult<Vec<Option<JsonValue>>> {
+        PathExtractor::new(paths)?.extract(bytes)
+    }
+
+    /// Parses csv into one map per record. Duplicate column names get a
+    /// suffix, as [`CsvParser::parse`] explains.
     pub fn parse_csv(csv_string: &str) -> ParsonResult<Vec<HashMap<String, CsvValue>>> {
         let csv_parser = CsvParser::new(csv_string.as_bytes())?;
         csv_parser.parse()
     }
 
+    /// Parses csv from raw bytes, detecting the encoding like
+    /// [`Parson::parse_json_with_bytes`] does.
     pub fn parse_csv_with_bytes(bytes: &[u8]) -> ParsonResult<Vec<HashMap<String, CsvValue>>> {
         let csv_parser = CsvParser::new(bytes)?;
         csv_parser.parse()
     }
+
+    /// Parses the csv file at `path` like [`Parson::parse_json_file`] does.
+    pub fn parse_csv_file(path: impl AsRef<Path>) -> ParsonResult<Vec<HashMap<String, CsvValue>>> {
+        let path = path.as_ref();
+        let bytes = file::read(path)?;
+        Self::parse_csv_with_bytes(&bytes).map_err(|error| file::file_error(path, error.message))
+    }
+
+    /// Parses csv from raw bytes decoded as `options` says, e.g. from a
+    /// Windows-1252 export. Use [`CsvParser::with_options`] to also learn
+    /// which lines a lossy decode touched.
+    pub fn parse_csv_with_options(
+        bytes: &[u8],
+        options: &CsvOptions,
+    ) -> ParsonResult<Vec<HashMap<String, CsvValue>>> {
+        let csv_parser = CsvParser::with_options(bytes, options)?;
+        csv_parser.parse()
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn parse_json_async<R: AsyncRead + Unpin>(reader: R) -> ParsonResult<JsonValue> {
+        json::parse_async(reader).await
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn parse_csv_async<R: AsyncRead + Unpin>(
+        reader: R,
+    ) -> ParsonResult<Vec<HashMap<String, CsvValue>>> {
+        let csv_parser = CsvParser::new_async(reader).await?;
+        csv_parser.parse()
+    }
 }
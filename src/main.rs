@@ -0,0 +1,477 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap},
+    env, fs,
+    io::{self, Read, Write},
+    process::ExitCode,
+};
+
+use parson::{CsvParser, JsonLexer, JsonQuery, JsonTokenKind, JsonValue, Parson};
+
+const USAGE: &str = "usage: parson <command> [options] [FILE]
+
+commands:
+  validate [--format F]           check the input, reporting errors with their position
+  fmt [--minify] [--indent N]     pretty-print (2 spaces by default) or minify json
+  convert --to F [--from F]       convert between formats
+  head [-n N] [--from F] [--to F] print the first N records (10 by default)
+  query EXPR [--from F] [--raw]   run a jq-style expression, printing each result
+
+F is one of csv, json or ndjson. When not given, the input format is guessed
+from the file extension and defaults to json. FILE defaults to stdin.
+";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Csv,
+    Json,
+    Ndjson,
+}
+
+impl Format {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            "ndjson" | "jsonl" => Ok(Format::Ndjson),
+            _ => Err(format!("unknown format {}", name)),
+        }
+    }
+
+    fn guess(path: Option<&str>) -> Self {
+        path.and_then(|path| path.rsplit_once('.'))
+            .and_then(|(_, extension)| Self::parse(&extension.to_lowercase()).ok())
+            .unwrap_or(Format::Json)
+    }
+}
+
+#[derive(Debug)]
+struct Args {
+    command: String,
+    input: Option<String>,
+    from: Option<Format>,
+    to: Option<Format>,
+    indent: Option<usize>,
+    count: usize,
+    expression: Option<String>,
+    raw: bool,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let command = args.next().ok_or("missing command")?;
+    let mut parsed = Args {
+        command,
+        input: None,
+        from: None,
+        to: None,
+        indent: Some(2),
+        count: 10,
+        expression: None,
+        raw: false,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--format" | "--from" => parsed.from = Some(Format::parse(&value()?)?),
+            "--to" => parsed.to = Some(Format::parse(&value()?)?),
+            "--minify" => parsed.indent = None,
+            "--raw" => parsed.raw = true,
+            "--indent" => {
+                let indent = value()?;
+                parsed.indent = Some(
+                    indent
+                        .parse()
+                        .map_err(|_| format!("bad indent {}", indent))?,
+                )
+            }
+            "-n" => {
+                let count = value()?;
+                parsed.count = count.parse().map_err(|_| format!("bad count {}", count))?
+            }
+            "-" => parsed.input = None,
+            option if option.starts_with('-') => return Err(format!("unknown option {}", option)),
+            _ if parsed.command == "query" && parsed.expression.is_none() => {
+                parsed.expression = Some(arg)
+            }
+            _ if parsed.input.is_some() => return Err("only one input file is supported".into()),
+            _ => parsed.input = Some(arg),
+        }
+    }
+    Ok(parsed)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) if !matches!(args.command.as_str(), "-h" | "--help" | "help") => args,
+        Ok(_) => {
+            print!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprint!("parson: {}\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(&args) {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("parson: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<ExitCode, String> {
+    let bytes = read_input(args.input.as_deref())?;
+    let label = args.input.as_deref().unwrap_or("<stdin>");
+    let from = args
+        .from
+        .unwrap_or_else(|| Format::guess(args.input.as_deref()));
+    let output = match args.command.as_str() {
+        "validate" => return validate(&bytes, label, from),
+        "fmt" => {
+            if from != Format::Json {
+                return Err("fmt only formats json".to_string());
+            }
+            let text = as_text(&bytes)?;
+            if let Some(error) = Parson::parse_json_recovering(&text).errors.first() {
+                return Err(format!("{}: {}", label, error));
+            }
+            format_json(&text, args.indent)
+        }
+        "convert" => {
+            let to = args.to.ok_or("convert needs --to")?;
+            dump(
+                &load(&bytes, from)?,
+                to,
+                args.indent,
+                &key_order(&bytes, from, to),
+            )?
+        }
+        "head" => {
+            let records = match load(&bytes, from)? {
+                JsonValue::Array(records) => {
+                    JsonValue::Array(records.into_iter().take(args.count).collect())
+                }
+                value => value,
+            };
+            let to = args.to.unwrap_or(from);
+            dump(&records, to, args.indent, &key_order(&bytes, from, to))?
+        }
+        "query" => {
+            let expression = args
+                .expression
+                .as_deref()
+                .ok_or("query needs an expression")?;
+            let query = JsonQuery::parse(expression).map_err(|error| error.message)?;
+            let results = query
+                .run(&load(&bytes, from)?)
+                .map_err(|error| error.message)?;
+            results
+                .iter()
+                .map(|result| match result {
+                    JsonValue::String(string) if args.raw => format!("{}\n", string),
+                    result => format_json(&result.to_string(), args.indent),
+                })
+                .collect()
+        }
+        command => return Err(format!("unknown command {}", command)),
+    };
+    io::stdout()
+        .write_all(output.as_bytes())
+        .map_err(|error| error.to_string())?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn read_input(path: Option<&str>) -> Result<Vec<u8>, String> {
+    match path {
+        Some(path) => fs::read(path).map_err(|error| format!("{}: {}", path, error)),
+        None => {
+            let mut bytes = vec![];
+            io::stdin()
+                .read_to_end(&mut bytes)
+                .map_err(|error| format!("<stdin>: {}", error))?;
+            Ok(bytes)
+        }
+    }
+}
+
+fn as_text(bytes: &[u8]) -> Result<Cow<'_, str>, String> {
+    Parson::decode_json(bytes).map_err(|error| error.message)
+}
+
+fn validate(bytes: &[u8], label: &str, format: Format) -> Result<ExitCode, String> {
+    let mut valid = true;
+    match format {
+        Format::Json => {
+            for error in Parson::parse_json_recovering(&as_text(bytes)?).errors {
+                eprintln!("{}: {}", label, error);
+                valid = false;
+            }
+        }
+        Format::Ndjson => {
+            for (idx, line) in as_text(bytes)?.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                for error in Parson::parse_json_recovering(line).errors {
+                    eprintln!(
+                        "{}: line {}, column {}: {}",
+                        label,
+                        idx + 1,
+                        error.span.column,
+                        error.message
+                    );
+                    valid = false;
+                }
+            }
+        }
+        Format::Csv => {
+            if let Err(error) = Parson::parse_csv_with_bytes(bytes) {
+                eprintln!("{}: {}", label, error.message);
+                valid = false;
+            }
+        }
+    }
+    if !valid {
+        return Ok(ExitCode::FAILURE);
+    }
+    println!("{}: valid", label);
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Reads the input as a json value; csv and ndjson give an array of records.
+fn load(bytes: &[u8], format: Format) -> Result<JsonValue, String> {
+    match format {
+        Format::Json => Parson::parse_json_with_bytes(bytes).map_err(|error| error.message),
+        Format::Ndjson => as_text(bytes)?
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                Parson::parse_json(line)
+                    .map_err(|error| format!("line {}: {}", idx + 1, error.message))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(JsonValue::Array),
+        Format::Csv => Ok(JsonValue::Array(
+            Parson::parse_csv_with_bytes(bytes)
+                .map_err(|error| error.message)?
+                .into_iter()
+                .map(|row| {
+                    JsonValue::Object(
+                        row.into_iter()
+                            .map(|(key, value)| (key, value.into()))
+                            .collect::<HashMap<_, _>>(),
+                    )
+                })
+                .collect(),
+        )),
+    }
+}
+
+/// The column names of csv input, or the object keys of json input, in the
+/// order they first appear. Only csv output needs them.
+fn key_order(bytes: &[u8], from: Format, to: Format) -> Vec<String> {
+    if to != Format::Csv {
+        return vec![];
+    }
+    if from == Format::Csv {
+        return CsvParser::new(bytes)
+            .map(|parser| parser.header().to_vec())
+            .unwrap_or_default();
+    }
+    let Ok(text) = as_text(bytes) else {
+        return vec![];
+    };
+    let text = text.as_ref();
+    let lexemes = JsonLexer::new(text)
+        .filter(|lexeme| {
+            !matches!(
+                lexeme.kind,
+                JsonTokenKind::Whitespace | JsonTokenKind::Comment
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut keys = vec![];
+    for pair in lexemes.windows(2) {
+        if pair[0].kind != JsonTokenKind::String || pair[1].kind != JsonTokenKind::Colon {
+            continue;
+        }
+        if let Ok(JsonValue::String(key)) = Parson::parse_json(pair[0].text(text)) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+    keys
+}
+
+fn dump(
+    value: &JsonValue,
+    format: Format,
+    indent: Option<usize>,
+    key_order: &[String],
+) -> Result<String, String> {
+    match (format, value) {
+        (Format::Json, value) => Ok(format_json(&value.to_string(), indent)),
+        (Format::Ndjson, JsonValue::Array(records)) => Ok(records
+            .iter()
+            .map(|record| format!("{}\n", record))
+            .collect()),
+        (Format::Ndjson, value) => Ok(format!("{}\n", value)),
+        (Format::Csv, JsonValue::Array(records)) => write_csv(records, key_order),
+        (Format::Csv, _) => Err("csv output needs an array of objects".to_string()),
+    }
+}
+
+/// Writes the records with a column per key, in `key_order` and then by name.
+fn write_csv(records: &[JsonValue], key_order: &[String]) -> Result<String, String> {
+    let mut columns = BTreeSet::new();
+    for record in records {
+        match record {
+            JsonValue::Object(object) => columns.extend(object.keys()),
+            _ => return Err("csv output needs an array of objects".to_string()),
+        }
+    }
+    let mut columns = columns.into_iter().collect::<Vec<_>>();
+    columns.sort_by_key(|column| {
+        key_order
+            .iter()
+            .position(|key| key == *column)
+            .unwrap_or(usize::MAX)
+    });
+    let mut csv = columns
+        .iter()
+        .map(|column| csv_field(column, true))
+        .collect::<Vec<_>>()
+        .join(",");
+    csv.push('\n');
+    for record in records {
+        if let JsonValue::Object(object) = record {
+            let fields = columns
+                .iter()
+                .map(|column| match object.get(*column) {
+                    None | Some(JsonValue::Null) => String::new(),
+                    Some(JsonValue::String(string)) => csv_field(string, true),
+                    Some(value) => csv_field(&value.to_string(), false),
+                })
+                .collect::<Vec<_>>();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+    }
+    Ok(csv)
+}
+
+/// Quotes a field when it holds separators, or when it is a string that
+/// would read back as another type.
+fn csv_field(field: &str, is_string: bool) -> String {
+    let needs_quotes = field.contains([',', '"', '\n', '\r'])
+        || is_string
+            && (field.is_empty()
+                || field.parse::<f64>().is_ok()
+                || field == "true"
+                || field == "false");
+    match needs_quotes {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+/// Lays out valid json again, keeping key order and literals as written.
+/// Without an indent everything goes on one line with no spaces.
+fn format_json(source: &str, indent: Option<usize>) -> String {
+    let lexemes = JsonLexer::new(source)
+        .filter(|lexeme| {
+            !matches!(
+                lexeme.kind,
+                JsonTokenKind::Whitespace | JsonTokenKind::Comment
+            )
+        })
+        .collect::<Vec<_>>();
+    let is_open = |kind| {
+        matches!(
+            kind,
+            JsonTokenKind::OpenCurlyBracket | JsonTokenKind::OpenSquareBracket
+        )
+    };
+    let is_close = |kind| {
+        matches!(
+            kind,
+            JsonTokenKind::CloseCurlyBracket | JsonTokenKind::CloseSquareBracket
+        )
+    };
+    let newline = |out: &mut String, depth: usize| {
+        if let Some(width) = indent {
+            out.push('\n');
+            out.push_str(&" ".repeat(width * depth));
+        }
+    };
+
+    let mut out = String::with_capacity(source.len());
+    let mut depth = 0;
+    for (idx, lexeme) in lexemes.iter().enumerate() {
+        match lexeme.kind {
+            kind if is_open(kind) => {
+                out.push_str(lexeme.text(source));
+                if !lexemes.get(idx + 1).is_some_and(|next| is_close(next.kind)) {
+                    depth += 1;
+                    newline(&mut out, depth);
+                }
+            }
+            kind if is_close(kind) => {
+                if idx == 0 || !is_open(lexemes[idx - 1].kind) {
+                    depth = depth.saturating_sub(1);
+                    newline(&mut out, depth);
+                }
+                out.push_str(lexeme.text(source));
+            }
+            JsonTokenKind::Comma => {
+                out.push(',');
+                newline(&mut out, depth);
+            }
+            JsonTokenKind::Colon if indent.is_some() => out.push_str(": "),
+            _ => out.push_str(lexeme.text(source)),
+        }
+    }
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{csv_field, format_json, parse_args, Format};
+
+    #[test]
+    fn pretty_print_and_minify() {
+        let source = "{\"b\": [1.50, {}], \"a\" :[ ]}";
+        assert_eq!(
+            format_json(source, Some(2)),
+            "{\n  \"b\": [\n    1.50,\n    {}\n  ],\n  \"a\": []\n}\n"
+        );
+        assert_eq!(format_json(source, None), "{\"b\":[1.50,{}],\"a\":[]}\n");
+    }
+
+    #[test]
+    fn quote_csv_fields_when_needed() {
+        assert_eq!(csv_field("plain", true), "plain");
+        assert_eq!(csv_field("a,\"b\"", true), "\"a,\"\"b\"\"\"");
+        assert_eq!(csv_field("12", true), "\"12\"");
+        assert_eq!(csv_field("12", false), "12");
+        assert_eq!(csv_field("", true), "\"\"");
+    }
+
+    #[test]
+    fn parse_command_line() {
+        let args = ["head", "-n", "3", "data.csv", "--to", "ndjson"].map(String::from);
+        let args = parse_args(args.into_iter()).unwrap();
+        assert_eq!(args.count, 3);
+        assert_eq!(args.input.as_deref(), Some("data.csv"));
+        assert_eq!(args.to, Some(Format::Ndjson));
+        assert_eq!(Format::guess(args.input.as_deref()), Format::Csv);
+
+        let args = ["fmt", "--indent"].map(String::from);
+        assert!(parse_args(args.into_iter()).is_err());
+    }
+}
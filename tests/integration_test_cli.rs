@@ -0,0 +1,132 @@
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+fn parson(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_parson"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn validate_reports_positions() {
+    let output = parson(&["validate"], "{\"a\": 1,\n \"b\": }");
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "<stdin>: line 2, column 7: expected a value\n"
+    );
+
+    let output = parson(&["validate", "--format", "csv"], "a,b\n1,2\n");
+    assert!(output.status.success());
+
+    let output = parson(&["validate"], "\"a\" \"b\"");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn read_utf16_input() {
+    let mut path = std::env::temp_dir();
+    path.push(format!("parson-cli-utf16-{}.json", std::process::id()));
+    let mut bytes = vec![0xFF, 0xFE];
+    bytes.extend(
+        "[1, {\"a\": null}]"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes),
+    );
+    std::fs::write(&path, bytes).unwrap();
+    let path = path.to_str().unwrap();
+
+    assert!(parson(&["validate", path], "").status.success());
+    let output = parson(&["fmt", "--minify", path], "");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "[1,{\"a\":null}]\n"
+    );
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn convert_between_formats() {
+    let output = parson(
+        &["convert", "--from", "csv", "--to", "ndjson"],
+        "name,age\nann,30\n",
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "{\"age\":30,\"name\":\"ann\"}\n"
+    );
+
+    let output = parson(
+        &["convert", "--from", "ndjson", "--to", "csv"],
+        "{\"a\":1}\n{\"a\":\"x,y\"}\n",
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "a\n1\n\"x,y\"\n");
+
+    let output = parson(&["convert", "--to", "ndjson"], "[1,2]\n");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1\n2\n");
+
+    let output = parson(&["convert", "--to", "ndjson"], "{\"a\":\"x\\\"y\\n\"}\n");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "{\"a\":\"x\\\"y\\n\"}\n"
+    );
+
+    let output = parson(&["convert", "--from", "csv", "--to", "csv"], "b,a\n1,2\n");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "b,a\n1,2\n");
+    let output = parson(
+        &["convert", "--from", "ndjson", "--to", "csv"],
+        "{\"z\":1,\"y\":2}\n{\"x\":3,\"z\":4}\n",
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "z,y,x\n1,2,\n4,,3\n"
+    );
+}
+
+#[test]
+fn fmt_and_head_read_files() {
+    let mut path = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    path.push_str("/tests/test_files/MOCK_DATA.json");
+
+    let output = parson(&["head", "-n", "2", "--to", "ndjson", &path], "");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).lines().count(), 2);
+
+    let output = parson(&["fmt", "--minify"], "[1, {\"a\": null}]");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "[1,{\"a\":null}]\n"
+    );
+}
+
+#[test]
+fn query_input() {
+    let input = "[{\"name\": \"ann\", \"age\": 30}, {\"name\": \"bob\", \"age\": 20}]";
+    let output = parson(
+        &["query", "--raw", ".[] | select(.age > 25) | .name"],
+        input,
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ann\n");
+
+    let output = parson(&["query", "--raw", ".a"], "{\"a\": \"x\\\"y\"}\n");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "x\"y\n");
+
+    let output = parson(&["query", "map(.age) | add", "--minify"], input);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "50\n");
+
+    let output = parson(&["query", ".["], input);
+    assert!(!output.status.success());
+}
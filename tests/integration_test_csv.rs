@@ -1,6 +1,12 @@
-use std::fs::read;
+use std::{
+    fs::read,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use parson::{ParsingError, Parson, ParsonResult};
+use parson::{
+    CsvNumber, CsvOptions, CsvParser, CsvType, CsvValue, Date, DateFormat, ParsingError, Parson,
+    ParsonResult, Time,
+};
 
 #[test]
 fn read_csv_data_from_test_file() -> ParsonResult<()> {
@@ -16,3 +22,99 @@ fn read_csv_data_from_test_file() -> ParsonResult<()> {
 
     Ok(())
 }
+
+#[test]
+fn read_csv_test_file_by_path() -> ParsonResult<()> {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let parsed = Parson::parse_csv_file(format!("{}/tests/test_files/MOCK_DATA.csv", crate_dir))?;
+    let bytes = read(format!("{}/tests/test_files/MOCK_DATA.csv", crate_dir)).unwrap();
+    assert_eq!(parsed.len(), Parson::parse_csv_with_bytes(&bytes)?.len());
+
+    let missing = format!("{}/tests/test_files/MISSING.csv", crate_dir);
+    let error = Parson::parse_csv_file(&missing).err().unwrap();
+    assert!(error.message.starts_with(&format!("{}: ", missing)));
+
+    Ok(())
+}
+
+#[test]
+fn read_csv_with_bom_and_utf16() -> ParsonResult<()> {
+    let mut with_bom = b"\xEF\xBB\xBF".to_vec();
+    with_bom.extend_from_slice("id,name\n1,Zoë\n".as_bytes());
+    let parsed_csv = Parson::parse_csv_with_bytes(&with_bom)?;
+    assert!(parsed_csv[0].contains_key("id"));
+
+    let utf16 = "\u{feff}id,name\n1,Zoë\n"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect::<Vec<_>>();
+    let parsed_utf16 = Parson::parse_csv_with_bytes(&utf16)?;
+    assert_eq!(
+        format!("{:?}", parsed_utf16[0].get("name")),
+        format!("{:?}", parsed_csv[0].get("name"))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn keep_account_numbers_exact() -> ParsonResult<()> {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let path = format!("{}/tests/test_files/MOCK_DATA.csv", crate_dir);
+    let parsed = Parson::parse_csv_file(&path)?;
+    assert_eq!(
+        parsed[0]["account_number"],
+        CsvValue::Number(CsvNumber::Int(3588358312619246))
+    );
+
+    let options = CsvOptions::new()
+        .column_type("account_number", CsvType::String)
+        .column_type("transaction_amount", CsvType::Decimal);
+    let bytes = read(&path).unwrap();
+    let parsed = Parson::parse_csv_with_options(&bytes, &options)?;
+    assert_eq!(
+        parsed[0]["account_number"],
+        CsvValue::String("3588358312619246".to_string())
+    );
+    assert_eq!(
+        parsed[0]["transaction_amount"],
+        CsvValue::Number(CsvNumber::Decimal("7323.93".to_string()))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn read_dates_and_times_from_test_file() -> ParsonResult<()> {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let bytes = read(format!("{}/tests/test_files/MOCK_DATA.csv", crate_dir)).unwrap();
+    let (schema, records) = CsvParser::new(&bytes)?.parse_with_schema()?;
+    let column = schema.column("transaction_date").unwrap();
+    assert_eq!(column.csv_type, CsvType::Date);
+    assert_eq!(column.format, Some(DateFormat::us_date()));
+    assert_eq!(
+        schema.column("transaction_approval_time").unwrap().csv_type,
+        CsvType::Time
+    );
+    assert_eq!(
+        records[0]["transaction_date"],
+        CsvValue::Date(Date::new(2021, 5, 22).unwrap())
+    );
+    assert_eq!(
+        records[0]["transaction_approval_time"],
+        CsvValue::Time(Time::new(20, 5, 0, 0).unwrap())
+    );
+    let instant = SystemTime::try_from(records[0]["transaction_date"].clone())?;
+    assert_eq!(instant, UNIX_EPOCH + Duration::from_secs(1_621_641_600));
+
+    let options = CsvOptions::new().column_format("transaction_date", DateFormat::european_date());
+    let error = CsvParser::with_options(&bytes, &options)?
+        .parse_records()
+        .unwrap_err();
+    assert_eq!(
+        error.message,
+        "line 2, column \"transaction_date\": cannot read \"5/22/2021\" as date"
+    );
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use std::fs::read;
 
-use parson::{ParsingError, Parson, ParsonResult};
+use parson::{JsonPushParser, JsonValueBuilder, ParsingError, Parson, ParsonResult};
 
 #[test]
 fn read_json_data_from_test_file() -> ParsonResult<()> {
@@ -16,3 +16,105 @@ fn read_json_data_from_test_file() -> ParsonResult<()> {
 
     Ok(())
 }
+
+#[test]
+fn read_json_test_file_by_path() -> ParsonResult<()> {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let parsed = Parson::parse_json_file(format!("{}/tests/test_files/MOCK_DATA.json", crate_dir))?;
+    assert_eq!(
+        parsed,
+        Parson::parse_json_with_bytes(
+            &read(format!("{}/tests/test_files/MOCK_DATA.json", crate_dir)).unwrap()
+        )?
+    );
+
+    let missing = format!("{}/tests/test_files/MISSING.json", crate_dir);
+    let error = Parson::parse_json_file(&missing).err().unwrap();
+    assert!(error.message.starts_with(&format!("{}: ", missing)));
+
+    Ok(())
+}
+
+#[test]
+fn extract_json_paths_from_test_file() -> ParsonResult<()> {
+    let mut crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    crate_dir.push_str("/tests/test_files/MOCK_DATA.json");
+    let bytes = read(crate_dir).map_err(|_| ParsingError {
+        message: "failed to read test file".to_string(),
+    })?;
+
+    let extracted = Parson::extract_json(&bytes, &["/0/customer_name", "[999].currency", "/1000"])?;
+
+    assert_eq!(extracted.len(), 3);
+    assert!(extracted[0].is_some());
+    assert!(extracted[1].is_some());
+    assert!(extracted[2].is_none());
+
+    Ok(())
+}
+
+#[test]
+fn push_parse_test_file_in_chunks() -> ParsonResult<()> {
+    let mut crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    crate_dir.push_str("/tests/test_files/MOCK_DATA.json");
+    let bytes = read(crate_dir).map_err(|_| ParsingError {
+        message: "failed to read test file".to_string(),
+    })?;
+
+    let mut parser = JsonPushParser::new();
+    let mut builder = JsonValueBuilder::new();
+    let mut values = vec![];
+    for chunk in bytes.chunks(1000) {
+        values.extend(
+            parser
+                .feed(chunk)?
+                .into_iter()
+                .filter_map(|e| builder.push(e)),
+        );
+    }
+    values.extend(parser.finish()?.into_iter().filter_map(|e| builder.push(e)));
+
+    assert_eq!(values.len(), 1);
+    assert_eq!(values[0], Parson::parse_json_with_bytes(&bytes)?);
+
+    Ok(())
+}
+
+#[test]
+fn read_json_test_file_as_utf16() -> ParsonResult<()> {
+    let mut crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    crate_dir.push_str("/tests/test_files/MOCK_DATA.json");
+    let bytes = read(crate_dir).map_err(|_| ParsingError {
+        message: "failed to read test file".to_string(),
+    })?;
+    let text = String::from_utf8(bytes.clone()).unwrap();
+
+    let utf16 = text
+        .encode_utf16()
+        .flat_map(u16::to_be_bytes)
+        .collect::<Vec<_>>();
+    let mut with_bom = b"\xEF\xBB\xBF".to_vec();
+    with_bom.extend_from_slice(&bytes);
+
+    let expected = Parson::parse_json_with_bytes(&bytes)?;
+    assert_eq!(Parson::parse_json_with_bytes(&utf16)?, expected);
+    assert_eq!(Parson::parse_json_with_bytes(&with_bom)?, expected);
+
+    Ok(())
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_parse_matches_sequential() -> ParsonResult<()> {
+    let mut crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    crate_dir.push_str("/tests/test_files/MOCK_DATA.json");
+    let bytes = read(crate_dir).map_err(|_| ParsingError {
+        message: "failed to read test file".to_string(),
+    })?;
+
+    assert_eq!(
+        Parson::parse_json_parallel(&bytes)?,
+        Parson::parse_json_with_bytes(&bytes)?
+    );
+    Ok(())
+}